@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_linklist::{fuzz, persistent};
+
+fuzz_target!(|data: &[u8]| fuzz::check::<persistent::List<i32>>(data));
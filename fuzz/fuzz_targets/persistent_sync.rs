@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_linklist::{fuzz, persistent_sync};
+
+fuzz_target!(|data: &[u8]| fuzz::check::<persistent_sync::List<i32>>(data));
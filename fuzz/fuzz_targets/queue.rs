@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_linklist::{fuzz, queue};
+
+fuzz_target!(|data: &[u8]| fuzz::check::<queue::List<i32>>(data));
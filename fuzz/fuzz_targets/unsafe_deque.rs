@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_linklist::{fuzz, unsafe_deque};
+
+fuzz_target!(|data: &[u8]| fuzz::check::<unsafe_deque::List<i32>>(data));
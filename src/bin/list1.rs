@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 #[derive(Debug)]
 enum List {
     // 因为List<T>的大小不确定, 所以放在Box里
@@ -1,3 +1,5 @@
+#![allow(dead_code, clippy::items_after_test_module)]
+
 use std::mem;
 
 #[derive(Debug)]
@@ -1,3 +1,5 @@
+#![allow(dead_code, clippy::items_after_test_module)]
+
 #[derive(Debug)]
 struct Node {
     elem: i32,
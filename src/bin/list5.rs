@@ -1,3 +1,5 @@
+#![allow(dead_code, clippy::items_after_test_module)]
+
 // 让链表支持泛型
 #[derive(Debug)]
 struct Node<T> {
@@ -1,3 +1,5 @@
+#![allow(dead_code, mismatched_lifetime_syntaxes, clippy::items_after_test_module)]
+
 #[derive(Debug)]
 struct Node<T> {
     elem: T,
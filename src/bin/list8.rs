@@ -1,3 +1,5 @@
+#![allow(dead_code, mismatched_lifetime_syntaxes, clippy::items_after_test_module, clippy::let_and_return, clippy::redundant_field_names)]
+
 use std::rc::Rc;
 
 #[derive(Debug)]
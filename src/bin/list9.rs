@@ -1,3 +1,5 @@
+#![allow(dead_code, mismatched_lifetime_syntaxes, clippy::items_after_test_module)]
+
 use std::cell::{Ref, RefCell, RefMut};
 use std::rc::Rc;
 
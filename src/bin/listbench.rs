@@ -0,0 +1,633 @@
+//! 比较各种链表和 `Vec`、`VecDeque`、`LinkedList` 的性能
+//!
+//! ```sh
+//! cargo run --release --bin listbench                           # 默认从 10 到 10M
+//! cargo run --release --bin listbench -- --sizes 1000,100000
+//! cargo run --release --bin listbench -- --only stack,vec --csv > bench.csv
+//! ```
+//!
+//! 每种操作都报告平均每个元素花的时间(ns)。n 比较小时会重复很多次, 总共处理大约一百万个元素,
+//! 计时只包含操作本身, 准备数据和释放内存的时间不算在内(drop 除外)。
+//! 不支持的操作(比如没有实现 `Clone` 的链表)显示为 `-`
+//!
+//! churn 是在同一个链表上反复 push n 个再 pop 光, 能看出复用内存的好处,
+//! 比如 `stack_pooled`(预留了节点池的栈)和普通 `stack` 的差别
+
+use rust_linklist::{deque, persistent, persistent_queue, persistent_sync, queue, stack, unsafe_deque};
+use std::collections::{LinkedList, VecDeque};
+use std::hint::black_box;
+use std::time::Instant;
+
+const DEFAULT_SIZES: &[usize] = &[10, 100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+// 每项测量总共处理的元素个数
+const BUDGET: usize = 1_000_000;
+
+const WORKLOADS: &[&str] = &["push", "pop", "churn", "iter", "peek", "drop", "clone"];
+
+// 被测的容器, 每种实现一次
+trait Subject: Sized + Default {
+    /// 在现有的链表上依次 push 0..n
+    fn refill(&mut self, n: usize);
+
+    /// 新建一个链表并依次 push 0..n
+    fn build(n: usize) -> Self {
+        let mut list = Self::default();
+        list.refill(n);
+        list
+    }
+
+    /// pop 到空为止
+    fn pop_all(&mut self);
+
+    /// 迭代一遍, 返回所有元素的和
+    fn sum(&self) -> u64;
+
+    /// 看一眼头部元素
+    fn peek(&self) -> u64;
+
+    /// clone 一份, 不支持时返回 None
+    fn duplicate(&self) -> Option<Self>;
+}
+
+impl Subject for stack::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_left(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_left() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+// 预留了节点池的栈, pop 出来的节点留给之后的 push, 和上面的 stack 对比就能看出节点池的效果
+#[derive(Default)]
+struct PooledStack(stack::List<u64>);
+
+impl Subject for PooledStack {
+    fn refill(&mut self, n: usize) {
+        self.0.refill(n);
+    }
+
+    // 节点在这里一次性分配好, 所以 push 这一项也包含了 with_capacity 的时间
+    fn build(n: usize) -> Self {
+        let mut list = PooledStack(stack::List::with_capacity(n));
+        list.refill(n);
+        list
+    }
+
+    fn pop_all(&mut self) {
+        self.0.pop_all();
+    }
+
+    fn sum(&self) -> u64 {
+        self.0.sum()
+    }
+
+    fn peek(&self) -> u64 {
+        self.0.peek()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        self.0.duplicate().map(PooledStack)
+    }
+}
+
+impl Subject for queue::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_right(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_left() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        None
+    }
+}
+
+impl Subject for deque::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_right(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_left() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().map(|elem| *elem.borrow()).sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        None
+    }
+}
+
+impl Subject for unsafe_deque::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_right(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_left() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        None
+    }
+}
+
+// 不可变链表的 clone 只复制头指针, 和其它实现的 clone 不是一回事, 但正是选型时要比较的
+impl Subject for persistent::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            *self = self.push_left(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(&elem) = self.peek_left() {
+            black_box(elem);
+            *self = self.pop_left();
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for persistent_sync::List<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            *self = self.push_left(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(&elem) = self.peek_left() {
+            black_box(elem);
+            *self = self.pop_left();
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for persistent_queue::Queue<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            *self = self.push_right(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(&elem) = self.peek_left() {
+            black_box(elem);
+            *self = self.pop_left();
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for persistent_queue::RealTimeQueue<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            *self = self.push_right(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(&elem) = self.peek_left() {
+            black_box(elem);
+            *self = self.pop_left();
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.peek_left().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for Vec<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.last().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for VecDeque<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_back(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_front() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.front().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl Subject for LinkedList<u64> {
+    fn refill(&mut self, n: usize) {
+        for i in 0..n as u64 {
+            self.push_back(i);
+        }
+    }
+
+    fn pop_all(&mut self) {
+        while let Some(elem) = self.pop_front() {
+            black_box(elem);
+        }
+    }
+
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn peek(&self) -> u64 {
+        *self.front().unwrap()
+    }
+
+    fn duplicate(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+// 测一种实现的所有操作, 返回每个元素的平均耗时(ns), 顺序同 WORKLOADS
+// n 比 budget 小时重复多次, 每项总共处理大约 budget 个元素
+fn measure<S: Subject>(n: usize, budget: usize) -> Vec<Option<f64>> {
+    let reps = (budget / n).max(1);
+    let per_elem = |start: Instant| Some(start.elapsed().as_nanos() as f64 / (reps * n) as f64);
+    let build_all = || (0..reps).map(|_| S::build(n)).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let lists = black_box(build_all());
+    let push = per_elem(start);
+    drop(lists);
+
+    let mut lists = build_all();
+    let start = Instant::now();
+    for list in &mut lists {
+        list.pop_all();
+    }
+    let pop = per_elem(start);
+    drop(lists);
+
+    // 同一个链表反复 push n 个再全部 pop 掉, 先来一轮让能复用内存的实现攒下内存
+    let mut list = S::build(n);
+    list.pop_all();
+    let start = Instant::now();
+    for _ in 0..reps {
+        list.refill(n);
+        list.pop_all();
+    }
+    let churn = per_elem(start);
+    drop(list);
+
+    let list = S::build(n);
+    let start = Instant::now();
+    for _ in 0..reps {
+        black_box(black_box(&list).sum());
+    }
+    let iter = per_elem(start);
+
+    let start = Instant::now();
+    for _ in 0..reps * n {
+        black_box(black_box(&list).peek());
+    }
+    let peek = per_elem(start);
+
+    // 不支持 clone 时第一次就返回 None, collect 直接结束
+    let start = Instant::now();
+    let copies: Option<Vec<S>> = (0..reps).map(|_| black_box(&list).duplicate()).collect();
+    let clone = copies.and_then(|copies| {
+        let clone = per_elem(start);
+        drop(copies);
+        clone
+    });
+    drop(list);
+
+    let lists = build_all();
+    let start = Instant::now();
+    drop(black_box(lists));
+    let drop_time = per_elem(start);
+
+    vec![push, pop, churn, iter, peek, drop_time, clone]
+}
+
+type Measure = fn(usize, usize) -> Vec<Option<f64>>;
+
+const IMPLS: &[(&str, Measure)] = &[
+    ("stack", measure::<stack::List<u64>>),
+    ("stack_pooled", measure::<PooledStack>),
+    ("queue", measure::<queue::List<u64>>),
+    ("deque", measure::<deque::List<u64>>),
+    ("unsafe_deque", measure::<unsafe_deque::List<u64>>),
+    ("persistent", measure::<persistent::List<u64>>),
+    ("persistent_sync", measure::<persistent_sync::List<u64>>),
+    ("persistent_queue", measure::<persistent_queue::Queue<u64>>),
+    ("realtime_queue", measure::<persistent_queue::RealTimeQueue<u64>>),
+    ("Vec", measure::<Vec<u64>>),
+    ("VecDeque", measure::<VecDeque<u64>>),
+    ("LinkedList", measure::<LinkedList<u64>>),
+];
+
+struct Row {
+    size: usize,
+    name: &'static str,
+    times: Vec<Option<f64>>,
+}
+
+struct Options {
+    sizes: Vec<usize>,
+    only: Option<Vec<String>>,
+    csv: bool,
+    budget: usize,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        sizes: DEFAULT_SIZES.to_vec(),
+        only: None,
+        csv: false,
+        budget: BUDGET,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => options.csv = true,
+            "--sizes" => {
+                let value = args.next().ok_or("--sizes needs a value, e.g. --sizes 10,1000")?;
+                options.sizes = value
+                    .split(',')
+                    .map(|s| match s.trim().replace('_', "").parse() {
+                        Ok(0) | Err(_) => Err(format!("invalid size `{s}`")),
+                        Ok(n) => Ok(n),
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--only" => {
+                let value = args.next().ok_or("--only needs a value, e.g. --only stack,Vec")?;
+                let names: Vec<String> = value.split(',').map(|s| s.trim().to_string()).collect();
+                for name in &names {
+                    if !IMPLS.iter().any(|(known, _)| known.eq_ignore_ascii_case(name)) {
+                        let known: Vec<&str> = IMPLS.iter().map(|(known, _)| *known).collect();
+                        return Err(format!("unknown impl `{name}`, expected one of: {}", known.join(", ")));
+                    }
+                }
+                options.only = Some(names);
+            }
+            _ => return Err(format!("unknown argument `{arg}`, usage: listbench [--sizes N,...] [--only IMPL,...] [--csv]")),
+        }
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Vec<Row> {
+    let selected = |name: &str| match &options.only {
+        None => true,
+        Some(names) => names.iter().any(|n| n.eq_ignore_ascii_case(name)),
+    };
+    let mut rows = Vec::new();
+    for &size in &options.sizes {
+        for &(name, measure) in IMPLS.iter().filter(|(name, _)| selected(name)) {
+            rows.push(Row {
+                size,
+                name,
+                times: measure(size, options.budget),
+            });
+        }
+    }
+    rows
+}
+
+fn format_time(time: Option<f64>) -> String {
+    match time {
+        None => "-".to_string(),
+        Some(t) if t >= 100.0 => format!("{t:.0}"),
+        Some(t) => format!("{t:.2}"),
+    }
+}
+
+// 每个 size 一张表, 行是实现, 列是操作
+fn table(rows: &[Row]) -> String {
+    let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0).max("impl".len());
+    let mut out = String::new();
+    let mut last_size = None;
+    for row in rows {
+        if last_size != Some(row.size) {
+            if last_size.is_some() {
+                out.push('\n');
+            }
+            last_size = Some(row.size);
+            out.push_str(&format!("n = {} (ns per elem)\n", row.size));
+            out.push_str(&format!("{:<name_width$}", "impl"));
+            for workload in WORKLOADS {
+                out.push_str(&format!(" {workload:>10}"));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("{:<name_width$}", row.name));
+        for &time in &row.times {
+            out.push_str(&format!(" {:>10}", format_time(time)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// 每个测量值一行, 方便用脚本对比不同版本的结果
+fn csv(rows: &[Row]) -> String {
+    let mut out = String::from("size,impl,workload,ns_per_elem\n");
+    for row in rows {
+        for (workload, time) in WORKLOADS.iter().zip(&row.times) {
+            let time = time.map_or(String::new(), |t| format!("{t:.3}"));
+            out.push_str(&format!("{},{},{},{}\n", row.size, row.name, workload, time));
+        }
+    }
+    out
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
+    if cfg!(debug_assertions) {
+        eprintln!("warning: built without optimizations, run with `cargo run --release --bin listbench`");
+    }
+    let rows = run(&options);
+    if options.csv {
+        print!("{}", csv(&rows));
+    } else {
+        print!("{}", table(&rows));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{csv, parse_args, run, table, IMPLS, WORKLOADS};
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn parse() {
+        let options = parse_args(args("--sizes 10,1_000 --only stack,vec --csv")).unwrap();
+        assert_eq!(options.sizes, vec![10, 1000]);
+        assert_eq!(options.only, Some(vec!["stack".to_string(), "vec".to_string()]));
+        assert!(options.csv);
+
+        assert!(parse_args(args("--sizes 0")).is_err());
+        assert!(parse_args(args("--sizes")).is_err());
+        assert!(parse_args(args("--only nothing")).is_err());
+        assert!(parse_args(args("--fast")).is_err());
+    }
+
+    #[test]
+    fn every_impl_and_workload() {
+        let mut options = parse_args(args("--sizes 3")).unwrap();
+        // 测试里只重复几次, 否则太慢
+        options.budget = 30;
+        let rows = run(&options);
+        assert_eq!(rows.len(), IMPLS.len());
+        for row in &rows {
+            assert_eq!(row.times.len(), WORKLOADS.len());
+            let unsupported = row.times.iter().filter(|t| t.is_none()).count();
+            let expected = usize::from(matches!(row.name, "queue" | "deque" | "unsafe_deque"));
+            assert_eq!(unsupported, expected, "{}", row.name);
+        }
+
+        let table = table(&rows);
+        assert!(table.starts_with("n = 3 (ns per elem)\nimpl "));
+        assert_eq!(table.lines().count(), IMPLS.len() + 2);
+
+        let csv = csv(&rows);
+        assert_eq!(csv.lines().count(), IMPLS.len() * WORKLOADS.len() + 1);
+        assert!(csv.contains("\n3,deque,clone,\n"));
+    }
+}
@@ -0,0 +1,817 @@
+//! 一行一条命令地操作各种链表, 也可以从 stdin 读入脚本, 方便复现问题
+//!
+//! ```sh
+//! cargo run --bin listctl             # 交互式
+//! cargo run --bin listctl < bug.txt   # 执行脚本, 有命令出错时退出码为 1
+//! ```
+//!
+//! 脚本的例子, `#` 开头的行是注释:
+//! ```text
+//! use persistent
+//! push_left 1 2
+//! snapshot old
+//! pop_left
+//! push_left 3
+//! show
+//! ```
+//! 元素都按字符串处理, 输入 `help` 查看所有命令。
+//! `model` 和 `fuzz` 报告的失败也是这样一段脚本, 可以直接拿来重放
+
+use rust_linklist::{deque, persistent, persistent_queue, persistent_sync, queue, stack, unsafe_deque};
+use std::fmt::Display;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+type Elem = String;
+
+const IMPLS: &[&str] = &[
+    "stack",
+    "queue",
+    "deque",
+    "unsafe_deque",
+    "persistent",
+    "persistent_sync",
+    "persistent_queue",
+    "realtime_queue",
+];
+
+const HELP: &str = "\
+commands:
+  use <impl>            switch to an empty list of the given implementation
+  push_left <elem>...   push each elem to the left end
+  push_right <elem>...  push each elem to the right end
+  pop_left, pop_right   remove and print one elem
+  peek, peek_left, peek_right
+  iter                  print all elems from left to right
+  iter_rev              print all elems from right to left
+  len
+  snapshot [name]       keep a copy of the current list, persistent lists share nodes with it
+  show                  print the memory layout of the list and its snapshots
+  dot                   print the same in Graphviz DOT
+  help, quit";
+
+// 当前选中的链表和它的快照
+#[derive(Default)]
+struct Session<L> {
+    list: L,
+    snapshots: Vec<(String, L)>,
+}
+
+// 各种链表支持的操作不一样, 不支持的返回错误
+trait Shell {
+    fn push_left(&mut self, _elem: Elem) -> Result<(), String> {
+        Err(unsupported("push_left"))
+    }
+
+    fn push_right(&mut self, _elem: Elem) -> Result<(), String> {
+        Err(unsupported("push_right"))
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        Err(unsupported("pop_left"))
+    }
+
+    fn pop_right(&mut self) -> Result<Option<Elem>, String> {
+        Err(unsupported("pop_right"))
+    }
+
+    fn peek_left(&self) -> Option<Elem>;
+
+    fn peek_right(&self) -> Result<Option<Elem>, String> {
+        Err(unsupported("peek_right"))
+    }
+
+    fn iter(&self) -> Vec<Elem>;
+
+    fn len(&self) -> usize;
+
+    fn snapshot(&mut self, name: String);
+
+    fn snapshots(&self) -> usize;
+
+    fn show(&self) -> String;
+
+    fn dot(&self) -> Result<String, String> {
+        Err(unsupported("dot"))
+    }
+}
+
+fn unsupported(op: &str) -> String {
+    format!("{op} is not supported by this list")
+}
+
+// 没有 layout() 的链表, 每个版本打印一行
+fn show_lines<L: Display>(list: &L, snapshots: &[(String, L)]) -> String {
+    let mut out = format!("[list] {list}\n");
+    for (name, snapshot) in snapshots {
+        out.push_str(&format!("[{name}] {snapshot}\n"));
+    }
+    out
+}
+
+impl<L> Session<L> {
+    // 当前链表排在最前面, 后面是各个快照
+    fn versions(&self) -> Vec<(&str, &L)> {
+        let mut versions = vec![("list", &self.list)];
+        versions.extend(self.snapshots.iter().map(|(name, list)| (name.as_str(), list)));
+        versions
+    }
+}
+
+impl Shell for Session<stack::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_left())
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        self.snapshots.push((name, self.list.clone()));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        stack::List::layout(&self.versions())
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(stack::List::to_dot(&self.versions()))
+    }
+}
+
+impl Shell for Session<queue::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn push_right(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_right(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_left())
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn peek_right(&self) -> Result<Option<Elem>, String> {
+        Ok(self.list.peek_right().cloned())
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        let mut copy = queue::List::new();
+        for elem in self.list.iter() {
+            copy.push_right(elem.clone());
+        }
+        self.snapshots.push((name, copy));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        show_lines(&self.list, &self.snapshots)
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(queue::List::to_dot(&self.versions()))
+    }
+}
+
+impl Shell for Session<deque::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn push_right(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_right(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_left())
+    }
+
+    fn pop_right(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_right())
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().map(|elem| elem.clone())
+    }
+
+    fn peek_right(&self) -> Result<Option<Elem>, String> {
+        Ok(self.list.peek_right().map(|elem| elem.clone()))
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().map(|elem| elem.borrow().clone()).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        let mut copy = deque::List::new();
+        for elem in self.iter() {
+            copy.push_right(elem);
+        }
+        self.snapshots.push((name, copy));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        show_lines(&self.list, &self.snapshots)
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(deque::List::to_dot(&self.versions()))
+    }
+}
+
+impl Shell for Session<unsafe_deque::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn push_right(&mut self, elem: Elem) -> Result<(), String> {
+        self.list.push_right(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_left())
+    }
+
+    fn pop_right(&mut self) -> Result<Option<Elem>, String> {
+        Ok(self.list.pop_right())
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn peek_right(&self) -> Result<Option<Elem>, String> {
+        Ok(self.list.peek_right().cloned())
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        let mut copy = unsafe_deque::List::new();
+        for elem in self.list.iter() {
+            copy.push_right(elem.clone());
+        }
+        self.snapshots.push((name, copy));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        show_lines(&self.list, &self.snapshots)
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(unsafe_deque::List::to_dot(&self.versions()))
+    }
+}
+
+// 不可变链表的快照只是多持有一个版本, 和当前链表共享节点
+impl Shell for Session<persistent::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list = self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        let head = self.peek_left();
+        self.list = self.list.pop_left();
+        Ok(head)
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        self.snapshots.push((name, self.list.clone()));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        persistent::List::layout(&self.versions())
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(persistent::List::to_dot(&self.versions()))
+    }
+}
+
+impl Shell for Session<persistent_sync::List<Elem>> {
+    fn push_left(&mut self, elem: Elem) -> Result<(), String> {
+        self.list = self.list.push_left(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        let head = self.peek_left();
+        self.list = self.list.pop_left();
+        Ok(head)
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        self.snapshots.push((name, self.list.clone()));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        persistent_sync::List::layout(&self.versions())
+    }
+
+    fn dot(&self) -> Result<String, String> {
+        Ok(persistent_sync::List::to_dot(&self.versions()))
+    }
+}
+
+impl Shell for Session<persistent_queue::Queue<Elem>> {
+    fn push_right(&mut self, elem: Elem) -> Result<(), String> {
+        self.list = self.list.push_right(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        let head = self.peek_left();
+        self.list = self.list.pop_left();
+        Ok(head)
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        self.snapshots.push((name, self.list.clone()));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        show_lines(&self.list, &self.snapshots)
+    }
+}
+
+impl Shell for Session<persistent_queue::RealTimeQueue<Elem>> {
+    fn push_right(&mut self, elem: Elem) -> Result<(), String> {
+        self.list = self.list.push_right(elem);
+        Ok(())
+    }
+
+    fn pop_left(&mut self) -> Result<Option<Elem>, String> {
+        let head = self.peek_left();
+        self.list = self.list.pop_left();
+        Ok(head)
+    }
+
+    fn peek_left(&self) -> Option<Elem> {
+        self.list.peek_left().cloned()
+    }
+
+    fn iter(&self) -> Vec<Elem> {
+        self.list.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn snapshot(&mut self, name: String) {
+        self.snapshots.push((name, self.list.clone()));
+    }
+
+    fn snapshots(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn show(&self) -> String {
+        show_lines(&self.list, &self.snapshots)
+    }
+}
+
+fn open(name: &str) -> Option<Box<dyn Shell>> {
+    let shell: Box<dyn Shell> = match name {
+        "stack" => Box::new(Session::<stack::List<Elem>>::default()),
+        "queue" => Box::new(Session::<queue::List<Elem>>::default()),
+        "deque" => Box::new(Session::<deque::List<Elem>>::default()),
+        "unsafe_deque" => Box::new(Session::<unsafe_deque::List<Elem>>::default()),
+        "persistent" => Box::new(Session::<persistent::List<Elem>>::default()),
+        "persistent_sync" => Box::new(Session::<persistent_sync::List<Elem>>::default()),
+        "persistent_queue" => Box::new(Session::<persistent_queue::Queue<Elem>>::default()),
+        "realtime_queue" => Box::new(Session::<persistent_queue::RealTimeQueue<Elem>>::default()),
+        _ => return None,
+    };
+    Some(shell)
+}
+
+fn show_elem(elem: Option<Elem>) -> String {
+    elem.unwrap_or_else(|| "None".to_string())
+}
+
+enum Step {
+    Continue,
+    Quit,
+}
+
+// 执行一行命令, 要打印的内容写进 out
+fn exec(shell: &mut Option<Box<dyn Shell>>, line: &str, out: &mut String) -> Result<Step, String> {
+    let mut words = line.split_whitespace();
+    let Some(cmd) = words.next() else {
+        return Ok(Step::Continue);
+    };
+    let args: Vec<&str> = words.collect();
+
+    match cmd {
+        "help" => {
+            out.push_str(HELP);
+            out.push_str(&format!("\nimpls: {}\n", IMPLS.join(", ")));
+            return Ok(Step::Continue);
+        }
+        "quit" | "exit" => return Ok(Step::Quit),
+        "use" => {
+            let [name] = args[..] else {
+                return Err("usage: use <impl>".to_string());
+            };
+            let opened = open(name).ok_or_else(|| format!("unknown impl `{name}`, expected one of: {}", IMPLS.join(", ")))?;
+            *shell = Some(opened);
+            return Ok(Step::Continue);
+        }
+        _ => {}
+    }
+
+    let list = shell.as_mut().ok_or("no list selected, run `use <impl>` first")?;
+    let no_args = || {
+        if args.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("`{cmd}` takes no arguments"))
+        }
+    };
+    match cmd {
+        "push_left" | "push_right" => {
+            if args.is_empty() {
+                return Err(format!("usage: {cmd} <elem>..."));
+            }
+            for elem in &args {
+                if cmd == "push_left" {
+                    list.push_left(elem.to_string())?;
+                } else {
+                    list.push_right(elem.to_string())?;
+                }
+            }
+        }
+        "pop_left" => {
+            no_args()?;
+            out.push_str(&show_elem(list.pop_left()?));
+            out.push('\n');
+        }
+        "pop_right" => {
+            no_args()?;
+            out.push_str(&show_elem(list.pop_right()?));
+            out.push('\n');
+        }
+        "peek" | "peek_left" => {
+            no_args()?;
+            out.push_str(&show_elem(list.peek_left()));
+            out.push('\n');
+        }
+        "peek_right" => {
+            no_args()?;
+            out.push_str(&show_elem(list.peek_right()?));
+            out.push('\n');
+        }
+        "iter" => {
+            no_args()?;
+            out.push_str(&format!("[{}]\n", list.iter().join(", ")));
+        }
+        "iter_rev" => {
+            no_args()?;
+            let mut elems = list.iter();
+            elems.reverse();
+            out.push_str(&format!("[{}]\n", elems.join(", ")));
+        }
+        "len" => {
+            no_args()?;
+            out.push_str(&format!("{}\n", list.len()));
+        }
+        "snapshot" => {
+            let name = match args[..] {
+                [] => format!("s{}", list.snapshots()),
+                [name] if name != "list" => name.to_string(),
+                _ => return Err("usage: snapshot [name], name can not be `list`".to_string()),
+            };
+            out.push_str(&format!("{name}\n"));
+            list.snapshot(name);
+        }
+        "show" => {
+            no_args()?;
+            out.push_str(&list.show());
+        }
+        "dot" => {
+            no_args()?;
+            out.push_str(&list.dot()?);
+        }
+        _ => return Err(format!("unknown command `{cmd}`, run `help` to list all commands")),
+    }
+    Ok(Step::Continue)
+}
+
+// 逐行执行, 返回出错的命令数
+fn run<R: BufRead, W: Write>(input: R, output: &mut W, prompt: bool) -> io::Result<usize> {
+    let mut shell = None;
+    let mut errors = 0;
+    let mut lines = input.lines();
+    loop {
+        if prompt {
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut out = String::new();
+        let step = exec(&mut shell, line, &mut out);
+        output.write_all(out.as_bytes())?;
+        match step {
+            Ok(Step::Continue) => {}
+            Ok(Step::Quit) => break,
+            Err(e) => {
+                errors += 1;
+                writeln!(output, "error: {e}")?;
+            }
+        }
+    }
+    Ok(errors)
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    // 只有交互式使用时才打印提示符, 脚本的输出里只有命令的结果
+    let interactive = stdin.is_terminal();
+    if interactive {
+        println!("type `help` to list all commands");
+    }
+    let errors = run(stdin.lock(), &mut io::stdout(), interactive)?;
+    if errors > 0 && !interactive {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, IMPLS};
+
+    fn script(input: &str) -> (String, usize) {
+        let mut out = Vec::new();
+        let errors = run(input.as_bytes(), &mut out, false).unwrap();
+        (String::from_utf8(out).unwrap(), errors)
+    }
+
+    #[test]
+    fn persistent_snapshots_share_nodes() {
+        let (out, errors) = script(
+            "\
+# list8.rs 注释里的例子
+use persistent
+push_left D C B
+snapshot list2
+push_left A
+snapshot list1
+pop_left
+pop_left
+
+push_left X
+show
+iter
+len
+",
+        );
+        assert_eq!(errors, 0);
+        assert_eq!(
+            out,
+            "\
+list2
+list1
+A
+B
+[list] --> (X, ptr) -------------> (C, ptr) -> (D, null)
+                                    ^
+[list2] -------------> (B, ptr) ----+
+                        ^
+[list1] -> (A, ptr) ----+
+[X, C, D]
+3
+"
+        );
+    }
+
+    #[test]
+    fn deque_ops() {
+        let (out, errors) = script(
+            "\
+use deque
+push_right 2 3
+push_left 1
+peek
+peek_right
+pop_right
+snapshot
+pop_left
+show
+",
+        );
+        assert_eq!(errors, 0);
+        assert_eq!(out, "1\n3\n3\ns0\n1\n[list] [2]\n[s0] [1 <-> 2]\n");
+    }
+
+    #[test]
+    fn stack_show_and_dot() {
+        let (out, errors) = script("use stack\npush_left 2 1\nshow\ndot\npop_left\npop_left\npop_left\nshow\n");
+        assert_eq!(errors, 0);
+        assert!(out.starts_with("[list] -> (1, ptr) -> (2, null)\ndigraph list {\n"));
+        assert!(out.ends_with("}\n1\n2\nNone\n[list] -> null\n"));
+    }
+
+    #[test]
+    fn replays_model_reports() {
+        use rust_linklist::deque;
+        use rust_linklist::model::{self, Op, Subject};
+
+        // iter_rev 没有倒过来的 deque, NAME 用真实的实现名, 报告才能在 listctl 里重放
+        #[derive(Default)]
+        struct Unreversed(deque::List<i32>);
+
+        impl Subject for Unreversed {
+            const NAME: &'static str = "deque";
+
+            fn supports(op: &Op) -> bool {
+                <deque::List<i32> as Subject>::supports(op)
+            }
+
+            fn apply(&mut self, op: &Op) -> model::Output {
+                match op {
+                    Op::IterRev => self.0.apply(&Op::Iter),
+                    _ => self.0.apply(op),
+                }
+            }
+        }
+
+        let report = model::check::<Unreversed>(1, 200).unwrap_err().to_string();
+        assert_eq!(
+            report,
+            "\
+use deque
+# diverged from VecDeque (seed 1) after 3 ops:
+push_right 0
+push_left 608
+iter_rev
+# expected Elems([0, 608]), got Elems([608, 0])"
+        );
+        // listctl 里的 iter_rev 给出的是正确的结果
+        let (out, errors) = script(&report);
+        assert_eq!(errors, 0);
+        assert_eq!(out, "[0, 608]\n");
+    }
+
+    #[test]
+    fn errors_do_not_stop_the_script() {
+        let (out, errors) = script(
+            "\
+push_left 1
+use nothing
+use persistent_queue
+push_left 1
+push_right 1 2
+pop_right
+frobnicate
+peek 1
+snapshot list
+dot
+iter
+quit
+iter
+",
+        );
+        assert_eq!(errors, 8);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "error: no list selected, run `use <impl>` first");
+        assert!(lines[1].starts_with("error: unknown impl `nothing`"));
+        assert_eq!(lines[2], "error: push_left is not supported by this list");
+        assert_eq!(lines[3], "error: pop_right is not supported by this list");
+        assert!(lines[4].starts_with("error: unknown command `frobnicate`"));
+        assert_eq!(lines[5], "error: `peek` takes no arguments");
+        assert!(lines[6].starts_with("error: usage: snapshot"));
+        assert_eq!(lines[7], "error: dot is not supported by this list");
+        // quit 之后的命令不再执行
+        assert_eq!(lines[8], "[1, 2]");
+    }
+
+    #[test]
+    fn every_impl_runs_the_same_queue_script() {
+        for name in IMPLS {
+            let (out, errors) = script(&format!("use {name}\npush_right a b c\npop_left\nsnapshot\npop_left\niter\nlen\nshow\n"));
+            let supports_push_right = *name != "stack" && *name != "persistent" && *name != "persistent_sync";
+            if supports_push_right {
+                assert_eq!(errors, 0, "{name}");
+                assert!(out.starts_with("a\ns0\nb\n[c]\n1\n"), "{name}: {out}");
+            } else {
+                assert_eq!(errors, 1, "{name}");
+            }
+        }
+    }
+}
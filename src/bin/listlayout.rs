@@ -0,0 +1,38 @@
+//! 用真实的链表画出 list1/list2/list8 注释里手画的内存布局图
+//!
+//! ```sh
+//! cargo run --bin listlayout          # 画出注释里的几个例子
+//! cargo run --bin listlayout 1 2 3    # 用参数构造一个栈再画出来
+//! ```
+
+use rust_linklist::{persistent, stack};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        let list: stack::List<String> = args.into_iter().collect();
+        print!("{}", stack::List::layout(&[("ptr", &list)]));
+        return;
+    }
+
+    // list1.rs / list2.rs
+    println!("stack::List:");
+    let list = stack::List::from([1, 2]);
+    print!("{}", stack::List::layout(&[("ptr", &list)]));
+    println!();
+
+    // list8.rs: 依次 push 三次, 每个版本共享上一个版本的全部节点
+    println!("persistent::List, push 三次:");
+    let list1 = persistent::List::new().push_left("A");
+    let list2 = list1.push_left("B");
+    let list3 = list2.push_left("C");
+    print!("{}", persistent::List::layout(&[("list3", &list3), ("list2", &list2), ("list1", &list1)]));
+    println!();
+
+    // list8.rs 开头: B 被三个链表共享
+    println!("persistent::List, 共享尾部:");
+    let list2 = persistent::List::new().push_left("D").push_left("C").push_left("B");
+    let list1 = list2.push_left("A");
+    let list3 = list2.push_left("X");
+    print!("{}", persistent::List::layout(&[("list1", &list1), ("list2", &list2), ("list3", &list3)]));
+}
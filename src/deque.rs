@@ -0,0 +1,1457 @@
+//! 基于 `Rc<RefCell<Node<T>>>` 的双向链表 (对应 `src/bin/list9.rs`)
+
+use crate::dot::{Dot, Edge};
+use crate::range::to_indices;
+use crate::sort::{self, Sortable};
+use crate::teardown;
+use crate::traits::{Deque, Queue, Stack};
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::rc::Rc;
+
+// 双向链表
+pub(crate) struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+// 双向链表中的结点必然会被多个人持有, 所以需要Rc
+// Rc<T>是指向的不可变引用, 想要改变T的值, 可以用RefCell包裹T
+// RefCell<T>不会在堆上分配任何内存, 只是把T包了一层
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+/// 两端都可以 push/pop 的双向链表
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+impl<T> Node<T> {
+    fn new(value: T) -> Rc<RefCell<Node<T>>> {
+        let node = Node {
+            elem: value,
+            next: None,
+            prev: None,
+        };
+
+        Rc::new(RefCell::new(node))
+    }
+
+    // 拿出已经从链表上摘下来的节点里的元素
+    fn into_elem(node: Rc<RefCell<Node<T>>>) -> T {
+        // 这样是不行的 相当于是(&mut Node<T>).elem, 没发通过引用来move
+        // node.borrow_mut().elem
+
+        // 拿到Rc里面的东西, 再用into_inner消耗掉RefCell, 拿到T
+        // 链表之外只有被 mem::forget 掉的元素句柄、迭代器或 ExtractIf 还会持有节点的 Rc, 见 ElemRef 前面的说明
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().elem,
+            Err(_) => panic!("cannot remove an element whose node is still held by a value leaked with mem::forget"),
+        }
+    }
+}
+
+impl<T> List<T> {
+    /// 创建一个空链表
+    pub fn new() -> Self {
+        List {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    /// 元素个数, O(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 在链表头部添加元素
+    pub fn push_left(&mut self, value: T) {
+        let new_head = Node::new(value);
+        match self.head.take() {
+            Some(old_head) => {
+                old_head.borrow_mut().prev = Some(new_head.clone());
+                new_head.borrow_mut().next = Some(old_head);
+                self.head = Some(new_head);
+            }
+            None => {
+                self.tail = Some(new_head.clone());
+                self.head = Some(new_head);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// 在链表尾部添加元素
+    pub fn push_right(&mut self, elem: T) {
+        let new_tail = Node::new(elem);
+        match self.tail.take() {
+            Some(old_tail) => {
+                old_tail.borrow_mut().next = Some(new_tail.clone());
+                new_tail.borrow_mut().prev = Some(old_tail);
+                self.tail = Some(new_tail);
+            }
+            None => {
+                self.head = Some(new_tail.clone());
+                self.tail = Some(new_tail);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// 从链表头部移出元素
+    ///
+    /// # Panics
+    ///
+    /// 节点还被 `mem::forget` 掉的元素句柄、迭代器或 [`ExtractIf`] 引用着时拿不出元素, 会 panic,
+    /// 见 [`List::iter`]
+    pub fn pop_left(&mut self) -> Option<T> {
+        self.unlink_head().map(Node::into_elem)
+    }
+
+    // 把头部节点从链表上摘下来, 不取出元素
+    fn unlink_head(&mut self) -> Link<T> {
+        self.head.take().inspect(|old_head| {
+            match old_head.borrow_mut().next.take() {
+                Some(new_head) => {
+                    new_head.borrow_mut().prev = None;
+                    self.head = Some(new_head);
+                }
+                None => {
+                    self.tail = None
+                }
+            };
+            self.len -= 1;
+        })
+    }
+
+    /// 从链表尾部移出元素
+    ///
+    /// # Panics
+    ///
+    /// 同 [`List::pop_left`]
+    pub fn pop_right(&mut self) -> Option<T> {
+        self.tail.take().map(|old_tail| {
+            match old_tail.borrow_mut().prev.take() {
+                Some(new_tail) => {
+                    new_tail.borrow_mut().next.take();
+                    self.tail = Some(new_tail);
+                }
+                None => {
+                    self.head.take();
+                }
+            }
+            self.len -= 1;
+            Node::into_elem(old_tail)
+        })
+    }
+
+    /// 返回链表头部元素
+    pub fn peek_left(&self) -> Option<Ref<'_, T>> {
+        self.head.as_ref().map(|node| {
+            // 这里是没法返回 &(node.elem) 作为 Option<&T> 的
+            // 因为node.borrow()是个局部变量, 没法返回局部变量的引用
+            // 所以退而求其次改为了返回 Option<Ref<T>>
+            Ref::map(node.borrow(), |n| &n.elem)
+        })
+    }
+
+    /// 返回链表头部元素, 可修改
+    pub fn peek_left_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head.as_ref().map(|node| {
+            RefMut::map(node.borrow_mut(), |node| &mut node.elem)
+        })
+    }
+
+    /// 返回链表尾部元素
+    pub fn peek_right(&self) -> Option<Ref<'_, T>> {
+        self.tail.as_ref().map(|node| {
+            Ref::map(node.borrow(), |node| &node.elem)
+        })
+    }
+
+    /// 返回链表尾部元素, 可修改
+    pub fn peek_right_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.tail.as_ref().map(|node| {
+            RefMut::map(node.borrow_mut(), |node| &mut node.elem)
+        })
+    }
+
+    /// 原地反转链表, 交换每个节点的 next 和 prev, 不会重新分配节点
+    pub fn reverse(&mut self) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let mut node = node.borrow_mut();
+            let Node { next, prev, .. } = &mut *node;
+            std::mem::swap(next, prev);
+            // 交换之后 prev 指向的才是原来的下一个节点
+            cur = node.prev.clone();
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// 把 `other` 的所有节点接到末尾, `other` 变为空, O(1)
+    pub fn append(&mut self, other: &mut Self) {
+        let Some(other_head) = other.head.take() else {
+            return;
+        };
+        match self.tail.take() {
+            Some(old_tail) => {
+                other_head.borrow_mut().prev = Some(old_tail.clone());
+                old_tail.borrow_mut().next = Some(other_head);
+            }
+            None => {
+                self.head = Some(other_head);
+            }
+        }
+        self.tail = other.tail.take();
+        self.len += std::mem::take(&mut other.len);
+    }
+
+    /// 从下标 `at` 处断开, 返回 `[at, len)` 部分, 自己只保留 `[0, at)`
+    ///
+    /// 从离 `at` 近的那一端开始找, 最多走 len / 2 步
+    ///
+    /// # Panics
+    ///
+    /// `at > len` 时 panic
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        if at == self.len {
+            return List::new();
+        }
+
+        // [at, len) 部分的第一个节点, 0 < at < len, 所以它前面一定还有节点
+        let node = self.node_at(at).unwrap();
+        let new_tail = node.borrow_mut().prev.take().unwrap();
+        new_tail.borrow_mut().next = None;
+        let rest = List {
+            head: Some(node),
+            tail: self.tail.replace(new_tail),
+            len: self.len - at,
+        };
+        self.len = at;
+        rest
+    }
+
+    // 返回下标为 index 的节点, index == len 时返回 None
+    // 从离 index 近的那一端开始找, 最多走 len / 2 步
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+        let mut node;
+        if index <= self.len / 2 {
+            node = self.head.clone().unwrap();
+            for _ in 0..index {
+                let next = node.borrow().next.clone().unwrap();
+                node = next;
+            }
+        } else {
+            node = self.tail.clone().unwrap();
+            for _ in index..self.len - 1 {
+                let prev = node.borrow().prev.clone().unwrap();
+                node = prev;
+            }
+        }
+        Some(node)
+    }
+
+    // 把 node 从链表上摘下来, 返回它的元素
+    // node 必须在这个链表里, 而且调用方手里不能再有它的其他 Rc, 否则 panic
+    fn unlink(&mut self, node: Rc<RefCell<Node<T>>>) -> T {
+        let (prev, next) = {
+            let mut node = node.borrow_mut();
+            (node.prev.take(), node.next.take())
+        };
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.clone(),
+            None => self.tail = prev.clone(),
+        }
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+        Node::into_elem(node)
+    }
+
+    /// 只保留 `f` 返回 `true` 的元素, 其余的节点从链表上摘下来释放
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|elem| f(elem))
+    }
+
+    /// 同 [`List::retain`], 但 `f` 可以修改元素
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        for _ in self.extract_if(|elem| !f(elem)) {}
+    }
+
+    /// 返回一个迭代器, 从头到尾检查每个元素, 把 `pred` 返回 `true` 的节点摘下来并返回元素
+    ///
+    /// 是惰性的: 迭代器中途被丢掉时, 还没检查的元素原样留在链表里
+    ///
+    /// 迭代器引用着下一个要检查的节点, 用 `mem::forget` 丢掉它之后, 移出这个元素的操作会 panic,
+    /// 见 [`List::iter`]
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(&mut self, pred: F) -> ExtractIf<'_, T, F> {
+        ExtractIf {
+            next: self.head.clone(),
+            list: self,
+            pred,
+        }
+    }
+
+    /// 移出下标在 `range` 内的元素, 返回按顺序产出它们的迭代器
+    ///
+    /// 每次 next 摘下一个节点; 迭代器被丢掉时, 范围内剩下的元素也会被移除
+    ///
+    /// # Panics
+    ///
+    /// 起点大于终点, 或者终点超过链表长度时 panic
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let (start, end) = to_indices(range, self.len);
+        Drain {
+            next: self.node_at(start),
+            list: self,
+            remaining: end - start,
+        }
+    }
+
+    /// 稳定排序, 见 [`List::sort_by`]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp)
+    }
+
+    /// 按 `f` 算出的 key 稳定排序, 见 [`List::sort_by`]
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// 用自底向上的归并排序原地排序, 稳定, O(n log n), 不分配内存
+    ///
+    /// 排序时只沿着 next 把节点当成单向链表处理, 最后再走一遍补上 prev 和 tail。
+    /// `compare` panic 时所有节点都会接回链表里, 链表依然可用, 只是顺序不确定
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        sort::sort_by(self, compare)
+    }
+}
+
+// 排序过程中这些链表只有 next 是有效的, prev 和 tail 在 restore 时统一重建
+impl<T> Sortable for List<T> {
+    type Node = Rc<RefCell<Node<T>>>;
+    type Elem = T;
+
+    fn take_nodes(&mut self) -> Link<T> {
+        self.tail = None;
+        self.head.take()
+    }
+
+    fn split_first(rest: &mut Link<T>) -> Link<T> {
+        rest.take().inspect(|node| {
+            let mut node = node.borrow_mut();
+            *rest = node.next.take();
+            node.prev = None;
+        })
+    }
+
+    fn merge<F: FnMut(&T, &T) -> bool>(
+        left: &mut Link<T>,
+        right: &mut Link<T>,
+        merged: &mut Link<T>,
+        is_less: &mut F,
+    ) {
+        // 已经合并好的部分的最后一个节点
+        let mut last: Link<T> = None;
+        loop {
+            let take_right = match (&*left, &*right) {
+                (Some(l), Some(r)) => is_less(&r.borrow().elem, &l.borrow().elem),
+                // 另一边已经空了, 剩下的整段直接接上
+                (Some(_), None) => {
+                    link_after(merged, &last, left.take());
+                    return;
+                }
+                (None, _) => {
+                    link_after(merged, &last, right.take());
+                    return;
+                }
+            };
+            let from = if take_right { &mut *right } else { &mut *left };
+            let node = from.take().unwrap();
+            *from = node.borrow_mut().next.take();
+            link_after(merged, &last, Some(node.clone()));
+            last = Some(node);
+        }
+    }
+
+    // 首尾相接的同时把每个节点的 prev 和链表的 tail 重新设好
+    fn restore(&mut self, pieces: impl Iterator<Item = Link<T>>) {
+        let mut prev: Link<T> = None;
+        for mut cur in pieces {
+            while let Some(node) = cur {
+                match &prev {
+                    Some(prev) => prev.borrow_mut().next = Some(node.clone()),
+                    None => self.head = Some(node.clone()),
+                }
+                cur = {
+                    let mut node = node.borrow_mut();
+                    node.prev = prev;
+                    node.next.take()
+                };
+                prev = Some(node);
+            }
+        }
+        self.tail = prev;
+    }
+}
+
+// 把 node 接在 last 后面, last 为空时 node 就是 merged 的第一个节点
+fn link_after<T>(merged: &mut Link<T>, last: &Link<T>, node: Link<T>) {
+    match last {
+        Some(last) => last.borrow_mut().next = node,
+        None => *merged = node,
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// derive(Debug) 在这里不只是会递归: next 和 prev 互相指向, 会在相邻两个节点之间来回打印停不下来
+// 手写的版本顺着 next 从头走到尾, Debug 输出 [1, 2, 3],
+// Display 输出 [1 <-> 2 <-> 3], 表示每对相邻节点之间都有两个方向的链接
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for elem in self.iter() {
+            list.entry(&*elem.borrow());
+        }
+        list.finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" <-> ")?;
+            }
+            fmt::Display::fmt(&*elem.borrow(), f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: fmt::Debug> List<T> {
+    /// 导出 Graphviz DOT 格式的内存布局图, 可以用 `dot -Tsvg` 渲染
+    ///
+    /// 画出 next 和 prev 两个方向的边、链表的 head/tail, 节点上标出 `Rc` 的强引用计数。
+    /// 节点正被可变借用时会 panic
+    pub fn to_dot(lists: &[(&str, &Self)]) -> String {
+        let mut dot = Dot::new();
+        for (label, list) in lists {
+            let handle = dot.head(label);
+            let mut from = handle.clone();
+            let mut edge = Edge::Head;
+            let mut cur = list.head.clone();
+            while let Some(rc) = cur {
+                let node = rc.borrow();
+                // 减去 rc 这个遍历时临时克隆出来的引用
+                let strong = Rc::strong_count(&rc) - 1;
+                let (name, fresh) = dot.node(Rc::as_ptr(&rc), &node.elem, Some(strong));
+                dot.edge(&from, &name, edge);
+                if !fresh {
+                    break;
+                }
+                // 从 head 往后走, prev 指向的节点一定已经画过了
+                if let Some(prev) = node.prev.as_ref().and_then(|prev| dot.name(Rc::as_ptr(prev))) {
+                    dot.edge(&name, &prev, Edge::Prev);
+                }
+                from = name;
+                edge = Edge::Next;
+                cur = node.next.clone();
+            }
+            if let Some(tail) = list.tail.as_ref().and_then(|tail| dot.name(Rc::as_ptr(tail))) {
+                dot.edge(&handle, &tail, Edge::Tail);
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T> Stack<T> for List<T> {
+    type Ref<'a> = Ref<'a, T> where T: 'a;
+    type RefMut<'a> = RefMut<'a, T> where T: 'a;
+
+    fn push_left(&mut self, elem: T) {
+        List::push_left(self, elem)
+    }
+
+    fn pop_left(&mut self) -> Option<T> {
+        List::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<Ref<'_, T>> {
+        List::peek_left(self)
+    }
+
+    fn peek_left_mut(&mut self) -> Option<RefMut<'_, T>> {
+        List::peek_left_mut(self)
+    }
+
+    fn len(&self) -> usize {
+        List::len(self)
+    }
+}
+
+impl<T> Queue<T> for List<T> {
+    fn push_right(&mut self, elem: T) {
+        List::push_right(self, elem)
+    }
+}
+
+impl<T> Deque<T> for List<T> {
+    fn pop_right(&mut self) -> Option<T> {
+        List::pop_right(self)
+    }
+
+    fn peek_right(&self) -> Option<Ref<'_, T>> {
+        List::peek_right(self)
+    }
+
+    fn peek_right_mut(&mut self) -> Option<RefMut<'_, T>> {
+        List::peek_right_mut(self)
+    }
+}
+
+// 节点之间 next 和 prev 互相持有 Rc, 形成了循环引用
+// 默认的drop只会把 head 和 tail 的强引用计数减一, 节点永远不会被释放
+// 所以需要手动把节点一个个pop出来, 循环而不是递归, 长链表也不会爆栈
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // 只摘节点不取元素, 还被 mem::forget 掉的句柄或迭代器引用着的节点直接泄漏, 而不是 panic
+        teardown::drop_all(self, List::unlink_head);
+    }
+}
+
+/// 按值迭代, 两端都可以取, 由 [`List::into_iter`](IntoIterator::into_iter) 创建
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_left()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+// 从后向前迭代
+// DoubleEndedIterator继承自Iterator
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_right()
+    }
+}
+
+/*
+借用的迭代器没法直接返回 Ref<'a, T>:
+下一个节点藏在当前节点的 RefCell 里, 从 Ref 守卫里拿出来的引用活不过守卫本身,
+所以拿不到一个生命周期为 'a 的 &RefCell<Node<T>>
+
+这里退而求其次, 迭代器持有节点的 Rc, 每次返回一个 ElemRef/ElemMut 句柄,
+需要读写元素时再通过句柄 borrow()/borrow_mut()
+句柄带着 'a 生命周期, 它们活着的时候链表被借用着, 不能 push/pop,
+所以 pop 里的 Rc::try_unwrap 不会因为句柄多持有的强引用而失败
+
+注意句柄和迭代器都要实现 Drop:
+Rc 的 drop 不会被借用检查器当作对 'a 的使用, 没有 Drop 的话,
+编译器允许在句柄被析构之前就去 pop 链表, 这时 try_unwrap 就会 panic
+实现了 Drop 之后, 析构本身就是一次使用, 借用会一直持续到句柄被释放
+
+唯一的例外是用 mem::forget 跳过析构。不只是句柄, 迭代器本身也持有 Rc:
+Iter/IterMut 的两个游标指向下一个要返回的节点, ExtractIf 的 next 指向下一个要检查的节点。
+它们被 forget 之后, 这些 Rc 永远不会释放, 节点里的元素再也拿不出来,
+之后 pop/retain/extract_if/drain 走到这个节点时会 panic。链表本身析构时不取元素, 不会 panic,
+这个节点和元素跟着被 forget 的值一起泄漏
+ */
+
+/// [`Iter`] 返回的元素句柄, 通过 [`borrow`](ElemRef::borrow) 读取元素
+pub struct ElemRef<'a, T> {
+    node: Rc<RefCell<Node<T>>>,
+    _marker: PhantomData<&'a List<T>>,
+}
+
+// 析构时要求 'a 依然有效, 见上面的说明
+impl<T> Drop for ElemRef<'_, T> {
+    fn drop(&mut self) {}
+}
+
+impl<T> ElemRef<'_, T> {
+    /// 借用元素
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.elem)
+    }
+}
+
+/// [`IterMut`] 返回的元素句柄, 可以读也可以改
+pub struct ElemMut<'a, T> {
+    node: Rc<RefCell<Node<T>>>,
+    _marker: PhantomData<&'a mut List<T>>,
+}
+
+// 析构时要求 'a 依然有效, 见上面的说明
+impl<T> Drop for ElemMut<'_, T> {
+    fn drop(&mut self) {}
+}
+
+impl<T> ElemMut<'_, T> {
+    /// 借用元素
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.elem)
+    }
+
+    /// 可变借用元素
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.elem)
+    }
+}
+
+// 两个方向的游标, 各自指向下一个要返回的节点
+// len 记录两个游标之间还剩几个节点, 减到 0 时迭代就结束了
+struct Cursors<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+}
+
+impl<T> Cursors<T> {
+    fn new(list: &List<T>) -> Self {
+        Cursors {
+            front: list.head.clone(),
+            back: list.tail.clone(),
+            len: list.len,
+        }
+    }
+
+    fn next(&mut self) -> Link<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.front.take().inspect(|node| {
+            self.front = node.borrow().next.clone();
+        })
+    }
+
+    fn next_back(&mut self) -> Link<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.back.take().inspect(|node| {
+            self.back = node.borrow().prev.clone();
+        })
+    }
+}
+
+/// 按引用迭代, 两端都可以取, 由 [`List::iter`] 创建
+pub struct Iter<'a, T> {
+    cursors: Cursors<T>,
+    _marker: PhantomData<&'a List<T>>,
+}
+
+// 析构时要求 'a 依然有效, 见上面的说明
+impl<T> Drop for Iter<'_, T> {
+    fn drop(&mut self) {}
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素句柄
+    ///
+    /// 句柄和迭代器本身都不能用 `mem::forget` 丢掉, 否则之后移出它们引用的元素的操作
+    /// (比如 [`List::pop_left`])会 panic: 句柄引用着自己的元素, 迭代器引用着两端下一个要返回的元素
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            cursors: Cursors::new(self),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = ElemRef<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursors.next().map(|node| ElemRef {
+            node,
+            _marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursors.len, Some(self.cursors.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.cursors.next_back().map(|node| ElemRef {
+            node,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// 按可变引用迭代, 两端都可以取, 由 [`List::iter_mut`] 创建
+pub struct IterMut<'a, T> {
+    cursors: Cursors<T>,
+    _marker: PhantomData<&'a mut List<T>>,
+}
+
+// 析构时要求 'a 依然有效, 见上面的说明
+impl<T> Drop for IterMut<'_, T> {
+    fn drop(&mut self) {}
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回可修改的元素句柄
+    ///
+    /// 和 [`List::iter`] 一样, 句柄和迭代器都不能用 `mem::forget` 丢掉
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            cursors: Cursors::new(self),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = ElemMut<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursors.next().map(|node| ElemMut {
+            node,
+            _marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursors.len, Some(self.cursors.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.cursors.next_back().map(|node| ElemMut {
+            node,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// 边迭代边摘掉满足条件的节点, 由 [`List::extract_if`] 创建
+pub struct ExtractIf<'a, T, F> {
+    list: &'a mut List<T>,
+    // 下一个要检查的节点
+    next: Link<T>,
+    pred: F,
+}
+
+// next 多持有一个 Rc, 和 Iter 一样需要 Drop 让借用持续到析构
+impl<T, F> Drop for ExtractIf<'_, T, F> {
+    fn drop(&mut self) {}
+}
+
+impl<T, F: FnMut(&mut T) -> bool> Iterator for ExtractIf<'_, T, F> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        while let Some(node) = self.next.take() {
+            self.next = node.borrow().next.clone();
+            if (self.pred)(&mut node.borrow_mut().elem) {
+                return Some(self.list.unlink(node));
+            }
+        }
+        None
+    }
+}
+
+/// 移出一段连续的元素, 由 [`List::drain`] 创建
+pub struct Drain<'a, T> {
+    list: &'a mut List<T>,
+    // 下一个要移出的节点
+    next: Link<T>,
+    remaining: usize,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.next.take().unwrap();
+        self.next = node.borrow().next.clone();
+        Some(self.list.unlink(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+// 没迭代完的部分也要移除
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // drop时给计数器加一, 用来检查元素有没有被释放
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn drop_frees_all_nodes() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list = List::new();
+        for i in 0..10 {
+            if i % 2 == 0 {
+                list.push_left(DropCounter(drops.clone()));
+            } else {
+                list.push_right(DropCounter(drops.clone()));
+            }
+        }
+        drop(list.pop_left());
+        drop(list.pop_right());
+        assert_eq!(drops.get(), 2);
+
+        drop(list);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn into_iter_drop_frees_rest() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list = List::new();
+        for _ in 0..5 {
+            list.push_right(DropCounter(drops.clone()));
+        }
+        let mut iter = list.into_iter();
+        drop(iter.next());
+        drop(iter.next_back());
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..100) {
+                list.push_right(elem);
+            }
+            list.push_left(tracker.elem(-1));
+            drop(list.pop_left());
+            drop(list.pop_right());
+            list.peek_right_mut().unwrap().value += 1;
+            for elem in list.iter() {
+                assert!(elem.borrow().value < 100);
+            }
+
+            let mut tail = list.split_off(50);
+            list.append(&mut tail);
+            list.reverse();
+            list.sort();
+            list.retain(|x| x.value % 2 == 0);
+            drop(list.extract_if(|x| x.value % 3 == 0).next());
+            drop(list.drain(5..20));
+
+            let mut iter = list.into_iter();
+            drop(iter.next());
+            drop(iter.next_back());
+            drop(iter);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, quiet_panic, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..10) {
+                list.push_right(elem);
+            }
+            list.push_right(tracker.panicking_elem(10));
+            for elem in tracker.elems(11..20) {
+                list.push_right(elem);
+            }
+            // 析构到一半 panic, 剩下的节点之间还有循环引用, 也要释放掉
+            assert!(panics(|| drop(list)));
+            tracker.assert_all_dropped_once();
+
+            let mut list = List::new();
+            for elem in tracker.elems(0..30) {
+                list.push_left(elem);
+            }
+            let mut calls = 0;
+            assert!(panics(|| list.sort_by(|a, b| {
+                calls += 1;
+                if calls > 20 {
+                    quiet_panic();
+                }
+                a.cmp(b)
+            })));
+            assert!(panics(|| list.retain(|x| x.value < 25 || quiet_panic())));
+            assert_eq!(list.len(), 30);
+            drop(list);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn fmt() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        assert_eq!(list.to_string(), "[]");
+        list.push_right(2);
+        list.push_right(3);
+        list.push_left(1);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(format!("{:#?}", list), "[\n    1,\n    2,\n    3,\n]");
+        assert_eq!(list.to_string(), "[1 <-> 2 <-> 3]");
+        assert_eq!(format!("{:>2}", list), "[ 1 <->  2 <->  3]");
+    }
+
+    #[test]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..1_000_000 {
+            list.push_right(i);
+        }
+        // 派生的 Debug 会在 next/prev 之间来回递归, 直到爆栈
+        assert!(format!("{:?}", list).ends_with(", 999998, 999999]"));
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(list.pop_left(), Some(999_999));
+        assert_eq!(list.pop_right(), Some(0));
+        drop(list);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next_back(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        let mut iter = list.iter();
+        assert_eq!(*iter.next().unwrap().borrow(), 1);
+        assert_eq!(*iter.next_back().unwrap().borrow(), 3);
+        assert_eq!(*iter.next().unwrap().borrow(), 2);
+        assert!(iter.next_back().is_none());
+        assert!(iter.next().is_none());
+
+        let rev: Vec<i32> = list.iter().rev().map(|e| *e.borrow()).collect();
+        assert_eq!(rev, vec![3, 2, 1]);
+
+        // 迭代不会消耗链表
+        drop(iter);
+        assert_eq!(list.pop_left(), Some(1));
+    }
+
+    #[test]
+    fn iter_meet_in_middle() {
+        for n in 0..6 {
+            let mut list = List::new();
+            for i in 0..n {
+                list.push_right(i);
+            }
+            // 两端交替取, 每个元素恰好出现一次
+            let mut iter = list.iter();
+            let mut seen = Vec::new();
+            while let Some(e) = iter.next() {
+                seen.push(*e.borrow());
+                match iter.next_back() {
+                    Some(e) => seen.push(*e.borrow()),
+                    None => break,
+                }
+            }
+            assert!(iter.next().is_none());
+            assert!(iter.next_back().is_none());
+            seen.sort();
+            assert_eq!(seen, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        for e in list.iter_mut() {
+            *e.borrow_mut() *= 10;
+        }
+        {
+            let mut iter = list.iter_mut();
+            let last = iter.next_back().unwrap();
+            *last.borrow_mut() += 1;
+            assert_eq!(*last.borrow(), 31);
+            assert_eq!(*iter.next().unwrap().borrow(), 10);
+            assert_eq!(*iter.next().unwrap().borrow(), 20);
+            assert!(iter.next().is_none());
+        }
+
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![10, 20, 31]);
+    }
+
+    // 故意泄漏了句柄, miri 会报内存泄漏
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn forgotten_handle() {
+        use crate::testkit::panics;
+
+        let mut list = List::new();
+        for i in 0..4 {
+            list.push_right(i.to_string());
+        }
+        std::mem::forget(list.iter().nth(1));
+        std::mem::forget(list.iter_mut().next_back());
+        assert_eq!(list.pop_left().as_deref(), Some("0"));
+        // 拿不出元素只能 panic, 但节点已经摘下来了, 链表还是完整的
+        assert!(panics(|| drop(list.pop_left())));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.iter().map(|e| e.borrow().clone()).collect::<Vec<_>>(), ["2", "3"]);
+        assert!(panics(|| drop(list.pop_right())));
+        assert_eq!(list.pop_right().as_deref(), Some("2"));
+        assert_eq!(list.len(), 0);
+
+        // 析构时不取元素, 不会 panic
+        let mut list = List::new();
+        for i in 0..4 {
+            list.push_right(i);
+        }
+        std::mem::forget(list.iter().nth(2));
+        drop(list);
+    }
+
+    // 迭代器本身也持有节点, 同上
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn forgotten_iter() {
+        use crate::testkit::panics;
+
+        let mut list = List::new();
+        for i in 0..6 {
+            list.push_right(i);
+        }
+        // 两个游标停在中间: front 指向 1, back 指向 4
+        let mut iter = list.iter();
+        iter.next();
+        iter.next_back();
+        std::mem::forget(iter);
+        assert_eq!(list.pop_left(), Some(0));
+        assert!(panics(|| {
+            list.pop_left();
+        }));
+        assert_eq!(list.pop_right(), Some(5));
+        assert!(panics(|| {
+            list.pop_right();
+        }));
+        assert_eq!(list.len(), 2);
+        drop(list);
+
+        // ExtractIf 的 next 指向下一个要检查的节点
+        let mut list = List::new();
+        for i in 0..4 {
+            list.push_right(i);
+        }
+        let mut extract = list.extract_if(|elem| *elem == 0);
+        assert_eq!(extract.next(), Some(0));
+        std::mem::forget(extract);
+        assert!(panics(|| {
+            list.pop_left();
+        }));
+        assert_eq!(list.pop_right(), Some(3));
+        drop(list);
+
+        // 析构时不取元素, 不会 panic
+        let mut list = List::new();
+        for i in 0..6 {
+            list.push_right(i);
+        }
+        let mut iter = list.iter_mut();
+        iter.next();
+        iter.next_back();
+        std::mem::forget(iter);
+        drop(list);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.push_left(2);
+        list.push_right(3);
+        list.push_left(1);
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next_back();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        drop(iter);
+        assert_eq!(list.iter_mut().skip(1).len(), 2);
+
+        list.pop_right();
+        assert_eq!(list.len(), 2);
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(iter.len(), 1);
+        iter.next_back();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    fn collect(list: &List<i32>) -> Vec<i32> {
+        list.iter().map(|e| *e.borrow()).collect()
+    }
+
+    fn from_vec(v: Vec<i32>) -> List<i32> {
+        let mut list = List::new();
+        for i in v {
+            list.push_right(i);
+        }
+        list
+    }
+
+    #[test]
+    fn reverse() {
+        for n in 0..5 {
+            let mut list = from_vec((0..n).collect());
+            let addr = list.peek_left().map(|e| &*e as *const i32);
+            list.reverse();
+            assert_eq!(collect(&list), (0..n).rev().collect::<Vec<_>>());
+            assert_eq!(list.len(), n as usize);
+            // 原来的头节点变成了尾节点, 节点本身没有换
+            assert_eq!(list.peek_right().map(|e| &*e as *const i32), addr);
+            // prev 也都反过来了
+            let back: Vec<i32> = list.iter().rev().map(|e| *e.borrow()).collect();
+            assert_eq!(back, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn append() {
+        let mut a = from_vec(vec![1, 2]);
+        let mut b = from_vec(vec![3, 4]);
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert!(b.peek_right().is_none());
+        assert_eq!(a.len(), 4);
+        assert_eq!(collect(&a), vec![1, 2, 3, 4]);
+        assert_eq!(a.iter().rev().map(|e| *e.borrow()).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+
+        let mut empty = List::new();
+        empty.append(&mut a);
+        assert!(a.is_empty());
+        assert_eq!(collect(&empty), vec![1, 2, 3, 4]);
+        empty.append(&mut a);
+        assert_eq!(empty.len(), 4);
+        assert_eq!(empty.pop_right(), Some(4));
+    }
+
+    #[test]
+    fn sort() {
+        for n in 0..20 {
+            let v: Vec<i32> = (0..n).map(|i| (i * 7) % 5).collect();
+            let mut list = from_vec(v.clone());
+            let mut expected = v;
+            expected.sort();
+            list.sort();
+            assert_eq!(collect(&list), expected);
+            assert_eq!(list.len(), n as usize);
+            let back: Vec<i32> = list.iter().rev().map(|e| *e.borrow()).collect();
+            assert_eq!(back, expected.into_iter().rev().collect::<Vec<_>>());
+        }
+
+        let mut list = from_vec(vec![3, 1, 2]);
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(collect(&list), vec![3, 2, 1]);
+        assert_eq!(list.pop_right(), Some(1));
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let mut list = List::new();
+        for i in 0..100 {
+            list.push_right((i * 37 % 10, i));
+        }
+        list.sort_by_key(|&(key, _)| key);
+        let mut expected: Vec<(i32, i32)> = (0..100).map(|i| (i * 37 % 10, i)).collect();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn sort_panic_keeps_all_nodes() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        for limit in [0, 1, 5, 20, 50] {
+            let mut list = from_vec((0..30).rev().collect());
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                list.sort_by(|a, b| {
+                    calls += 1;
+                    if calls > limit {
+                        panic!("comparator panicked");
+                    }
+                    a.cmp(b)
+                })
+            }));
+            assert!(result.is_err());
+            assert_eq!(list.len(), 30);
+            let mut elems = collect(&list);
+            elems.sort();
+            assert_eq!(elems, (0..30).collect::<Vec<_>>());
+            // prev 也是完整的
+            assert_eq!(list.iter().rev().count(), 30);
+            list.sort();
+            assert_eq!(list.pop_left(), Some(0));
+            assert_eq!(list.pop_right(), Some(29));
+        }
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = from_vec((0..10).collect());
+        list.retain(|&x| x % 3 != 0);
+        assert_eq!(collect(&list), vec![1, 2, 4, 5, 7, 8]);
+        assert_eq!(list.len(), 6);
+
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x > 40
+        });
+        assert_eq!(collect(&list), vec![50, 70, 80]);
+        assert_eq!(list.iter().rev().map(|e| *e.borrow()).collect::<Vec<_>>(), vec![80, 70, 50]);
+
+        list.retain(|_| false);
+        assert!(list.is_empty());
+        assert!(list.peek_left().is_none());
+        assert!(list.peek_right().is_none());
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut list = from_vec((0..10).collect());
+        let evens: Vec<i32> = list.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+        assert_eq!(collect(&list), vec![1, 3, 5, 7, 9]);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.pop_right(), Some(9));
+
+        // 惰性: 只摘掉迭代到的部分
+        let mut iter = list.extract_if(|x| *x > 1);
+        assert_eq!(iter.next(), Some(3));
+        drop(iter);
+        assert_eq!(collect(&list), vec![1, 5, 7]);
+        assert_eq!(list.pop_left(), Some(1));
+    }
+
+    #[test]
+    fn drain() {
+        let mut list = from_vec((0..10).collect());
+        let mut drain = list.drain(2..5);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some(2));
+        // 没迭代完的也会被移除
+        drop(drain);
+        assert_eq!(collect(&list), vec![0, 1, 5, 6, 7, 8, 9]);
+        assert_eq!(list.len(), 7);
+
+        // 从靠近尾部的位置开始
+        assert_eq!(list.drain(5..=6).collect::<Vec<_>>(), vec![8, 9]);
+        assert_eq!(list.peek_right().map(|e| *e), Some(7));
+        assert_eq!(list.drain(..1).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(list.drain(2..2).count(), 0);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), vec![1, 5, 6, 7]);
+        assert!(list.is_empty());
+        assert!(list.peek_right().is_none());
+        assert_eq!(list.drain(..).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        from_vec(vec![1, 2]).drain(1..3);
+    }
+
+    #[test]
+    fn split_off() {
+        for n in 0..6 {
+            for at in 0..=n {
+                let mut list = from_vec((0..n).collect());
+                let mut rest = list.split_off(at as usize);
+                assert_eq!(collect(&list), (0..at).collect::<Vec<_>>());
+                assert_eq!(collect(&rest), (at..n).collect::<Vec<_>>());
+                assert_eq!(list.len(), at as usize);
+                assert_eq!(rest.len(), (n - at) as usize);
+                // 两边的首尾都断干净了
+                assert_eq!(list.pop_right(), if at > 0 { Some(at - 1) } else { None });
+                assert_eq!(rest.pop_left(), if at < n { Some(at) } else { None });
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        from_vec(vec![1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert!(list.peek_left().is_none());
+        assert!(list.peek_right().is_none());
+        assert!(list.peek_left_mut().is_none());
+        assert!(list.peek_right_mut().is_none());
+
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+
+        assert_eq!(*list.peek_left().unwrap(), 3);
+        assert_eq!(*list.peek_left_mut().unwrap(), 3);
+        assert_eq!(*list.peek_right().unwrap(), 1);
+        assert_eq!(*list.peek_right_mut().unwrap(), 1);
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_left(), None);
+
+        // Populate list
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(3));
+        assert_eq!(list.pop_left(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_left(4);
+        list.push_left(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(5));
+        assert_eq!(list.pop_left(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_left(), Some(1));
+        assert_eq!(list.pop_left(), None);
+
+        // ---- back -----
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_right(), None);
+
+        // Populate list
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_right(), Some(3));
+        assert_eq!(list.pop_right(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_right(4);
+        list.push_right(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_right(), Some(5));
+        assert_eq!(list.pop_right(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_right(), Some(1));
+        assert_eq!(list.pop_right(), None);
+    }
+}
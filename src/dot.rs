@@ -0,0 +1,181 @@
+//! 各个链表的 `to_dot()` 共用的 Graphviz 输出工具
+//!
+//! 节点按第一次遇到的顺序编号为 n0, n1, ..., 链表句柄编号为 h0, h1, ...,
+//! 同样的输入总是得到同样的输出。
+//! 同一个节点被多个句柄经过时只画一次, 共享的尾部就表现为汇合到一起的箭头
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+pub(crate) struct Dot {
+    out: String,
+    // 节点地址 -> 编号
+    ids: HashMap<*const (), usize>,
+    heads: usize,
+}
+
+impl Dot {
+    pub(crate) fn new() -> Self {
+        let mut out = String::new();
+        out.push_str("digraph list {\n");
+        out.push_str("    rankdir=LR;\n");
+        out.push_str("    node [shape=box];\n");
+        Dot {
+            out,
+            ids: HashMap::new(),
+            heads: 0,
+        }
+    }
+
+    // 画一个链表句柄, 返回它的名字
+    pub(crate) fn head(&mut self, label: &str) -> String {
+        let name = format!("h{}", self.heads);
+        self.heads += 1;
+        let _ = writeln!(self.out, "    {name} [label=\"{}\", shape=plaintext];", escape(label));
+        name
+    }
+
+    // 画一个节点, 已经画过的不再重复画
+    // 返回节点的名字, 以及它是不是第一次出现
+    pub(crate) fn node<P, E>(&mut self, ptr: *const P, elem: &E, strong: Option<usize>) -> (String, bool)
+    where
+        E: fmt::Debug + ?Sized,
+    {
+        let ptr = ptr.cast::<()>();
+        if let Some(&id) = self.ids.get(&ptr) {
+            return (format!("n{id}"), false);
+        }
+        let id = self.ids.len();
+        self.ids.insert(ptr, id);
+        let mut label = escape(&format!("{elem:?}"));
+        if let Some(strong) = strong {
+            let _ = write!(label, "\\nrc={strong}");
+        }
+        let _ = writeln!(self.out, "    n{id} [label=\"{label}\"];");
+        (format!("n{id}"), true)
+    }
+
+    // 已经画过的节点的名字
+    pub(crate) fn name<P>(&self, ptr: *const P) -> Option<String> {
+        self.ids.get(&ptr.cast::<()>()).map(|id| format!("n{id}"))
+    }
+
+    pub(crate) fn edge(&mut self, from: &str, to: &str, kind: Edge) {
+        let attrs = match kind {
+            Edge::Head => "label=\"head\"",
+            Edge::Tail => "label=\"tail\", style=dotted",
+            Edge::Next => "label=\"next\"",
+            Edge::Prev => "label=\"prev\", style=dashed",
+        };
+        let _ = writeln!(self.out, "    {from} -> {to} [{attrs}];");
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Edge {
+    Head,
+    Tail,
+    Next,
+    Prev,
+}
+
+// DOT 的字符串里只需要转义 \ 和 "
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{deque, persistent, persistent_sync, queue, stack, unsafe_deque};
+
+    #[test]
+    fn stack_dot() {
+        let list: stack::List<i32> = (1..=2).collect();
+        assert_eq!(
+            stack::List::to_dot(&[("list", &list)]),
+            "digraph list {
+    rankdir=LR;
+    node [shape=box];
+    h0 [label=\"list\", shape=plaintext];
+    n0 [label=\"1\"];
+    h0 -> n0 [label=\"head\"];
+    n1 [label=\"2\"];
+    n0 -> n1 [label=\"next\"];
+}
+"
+        );
+        assert_eq!(
+            stack::List::<i32>::to_dot(&[("empty", &stack::List::new())]),
+            "digraph list {\n    rankdir=LR;\n    node [shape=box];\n    h0 [label=\"empty\", shape=plaintext];\n}\n"
+        );
+    }
+
+    #[test]
+    fn persistent_shared_tail() {
+        // list7/list8 注释里画的那种结构: B 被三个链表共享
+        let list2 = persistent::List::new().push_left("D").push_left("C").push_left("B");
+        let list1 = list2.push_left("A");
+        let list3 = list2.push_left("X");
+        let dot = persistent::List::to_dot(&[("list1", &list1), ("list2", &list2), ("list3", &list3)]);
+
+        // 每个节点只画一次
+        assert_eq!(dot.matches("[label=\"\\\"B\\\"\\nrc=3\"]").count(), 1);
+        assert!(dot.contains("n0 [label=\"\\\"A\\\"\\nrc=1\"];"));
+        assert!(dot.contains("n3 [label=\"\\\"D\\\"\\nrc=1\"];"));
+        // 三个句柄的箭头汇合到 B
+        assert!(dot.contains("n0 -> n1 [label=\"next\"];"));
+        assert!(dot.contains("h1 -> n1 [label=\"head\"];"));
+        assert!(dot.contains("n4 -> n1 [label=\"next\"];"));
+        assert_eq!(dot.matches("-> n1 ").count(), 3);
+        assert_eq!(dot.matches("label=\"next\"").count(), 4);
+    }
+
+    #[test]
+    fn persistent_sync_dot() {
+        let a = persistent_sync::List::new().push_left(1);
+        let b = a.push_left(2);
+        let dot = persistent_sync::List::to_dot(&[("a", &a), ("b", &b)]);
+        assert!(dot.contains("n0 [label=\"1\\nrc=2\"];"));
+        assert!(dot.contains("n1 -> n0 [label=\"next\"];"));
+    }
+
+    #[test]
+    fn deque_dot() {
+        let mut list = deque::List::new();
+        list.push_right(1);
+        list.push_right(2);
+        let dot = deque::List::to_dot(&[("list", &list)]);
+        // 头尾节点各被链表和相邻节点引用
+        assert!(dot.contains("n0 [label=\"1\\nrc=2\"];"));
+        assert!(dot.contains("n1 [label=\"2\\nrc=2\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"next\"];"));
+        assert!(dot.contains("n1 -> n0 [label=\"prev\", style=dashed];"));
+        assert!(dot.contains("h0 -> n1 [label=\"tail\", style=dotted];"));
+    }
+
+    #[test]
+    fn raw_pointer_lists_dot() {
+        let mut q = queue::List::new();
+        q.push_right(1);
+        q.push_right(2);
+        let dot = queue::List::to_dot(&[("q", &q)]);
+        assert!(dot.contains("n0 -> n1 [label=\"next\"];"));
+        assert!(dot.contains("h0 -> n1 [label=\"tail\", style=dotted];"));
+        assert!(!dot.contains("prev"));
+
+        let mut d = unsafe_deque::List::new();
+        d.push_right("a\"b");
+        d.push_right("c");
+        let dot = unsafe_deque::List::to_dot(&[("d", &d), ("again", &d)]);
+        assert!(dot.contains("n0 [label=\"\\\"a\\\\\\\"b\\\"\"];"));
+        assert!(dot.contains("n1 -> n0 [label=\"prev\", style=dashed];"));
+        // 第二个句柄指向同一个链表, 节点不重复
+        assert!(dot.contains("h1 -> n0 [label=\"head\"];"));
+        assert_eq!(dot.matches("[label=\"next\"]").count(), 1);
+    }
+}
@@ -0,0 +1,208 @@
+//! 模糊测试的入口, `fuzz/` 下的各个 target 和 `cargo test` 的语料回放都调用这里
+//!
+//! 任意字节串都能解码成一串操作: 每个操作占一个字节, push 还要再读一个字节作为元素。
+//! 每执行一步, 都会接着用 len/peek/iter 把链表的状态整个读一遍,
+//! 和 [`model`](crate::model) 里的 `VecDeque` 对比, 所以元素个数、两端的元素、
+//! 正反两个方向的迭代有任何一处对不上都能立刻发现
+//!
+//! ```
+//! use rust_linklist::{fuzz, unsafe_deque};
+//!
+//! fuzz::check::<unsafe_deque::List<i32>>(b"\x00\x07\x01\x09\x03");
+//! ```
+
+use crate::model::{self, Failure, Op, Subject};
+use crate::{deque, persistent, persistent_queue, persistent_sync, queue, stack, unsafe_deque};
+
+const KINDS: u8 = 9;
+
+/// 把字节串解码成操作, 任何输入都合法
+pub fn decode(data: &[u8]) -> Vec<Op> {
+    let mut bytes = data.iter().copied();
+    let mut ops = Vec::new();
+    while let Some(byte) = bytes.next() {
+        let op = match byte % KINDS {
+            0 => Op::PushLeft(i32::from(bytes.next().unwrap_or(0))),
+            1 => Op::PushRight(i32::from(bytes.next().unwrap_or(0))),
+            2 => Op::PopLeft,
+            3 => Op::PopRight,
+            4 => Op::PeekLeft,
+            5 => Op::PeekRight,
+            6 => Op::Iter,
+            7 => Op::IterRev,
+            _ => Op::Len,
+        };
+        ops.push(op);
+    }
+    ops
+}
+
+/// [`decode`] 的逆过程, 用来生成语料
+///
+/// push 的元素必须在 `0..=255` 之间, 否则 panic
+pub fn encode(ops: &[Op]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for op in ops {
+        match *op {
+            Op::PushLeft(elem) | Op::PushRight(elem) => {
+                let elem = u8::try_from(elem).expect("fuzz input can only push elems in 0..=255");
+                data.push(if matches!(op, Op::PushLeft(_)) { 0 } else { 1 });
+                data.push(elem);
+            }
+            Op::PopLeft => data.push(2),
+            Op::PopRight => data.push(3),
+            Op::PeekLeft => data.push(4),
+            Op::PeekRight => data.push(5),
+            Op::Iter => data.push(6),
+            Op::IterRev => data.push(7),
+            Op::Len => data.push(8),
+        }
+    }
+    data
+}
+
+// 去掉 S 不支持的操作, 并在每一步后面插入读取整个状态的操作
+fn expand<S: Subject>(ops: Vec<Op>) -> Vec<Op> {
+    let observe = [Op::Len, Op::PeekLeft, Op::PeekRight, Op::Iter, Op::IterRev];
+    let mut expanded = Vec::new();
+    for op in ops.into_iter().filter(S::supports) {
+        expanded.push(op);
+        expanded.extend(observe.iter().filter(|op| S::supports(op)).cloned());
+    }
+    expanded
+}
+
+/// 用 `data` 测试 `S`, 出错时返回缩减后的报告
+pub fn try_check<S: Subject>(data: &[u8]) -> Result<(), Box<Failure>> {
+    let ops = expand::<S>(decode(data));
+    if model::run::<S>(&ops).is_ok() {
+        return Ok(());
+    }
+    let (trace, divergence) = model::shrink::<S>(ops);
+    Err(Box::new(Failure {
+        name: S::NAME,
+        seed: None,
+        trace,
+        divergence,
+    }))
+}
+
+/// 同 [`try_check`], 但出错时直接 panic, 供 fuzz target 使用
+pub fn check<S: Subject>(data: &[u8]) {
+    if let Err(failure) = try_check::<S>(data) {
+        panic!("{failure}");
+    }
+}
+
+/// 用同一份输入测试所有链表
+pub fn check_all(data: &[u8]) {
+    check::<stack::List<i32>>(data);
+    check::<queue::List<i32>>(data);
+    check::<deque::List<i32>>(data);
+    check::<unsafe_deque::List<i32>>(data);
+    check::<persistent::List<i32>>(data);
+    check::<persistent_sync::List<i32>>(data);
+    check::<persistent_queue::Queue<i32>>(data);
+    check::<persistent_queue::RealTimeQueue<i32>>(data);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_all, decode, encode, expand, try_check};
+    use crate::model::{self, Op};
+    use crate::stack;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn decode_any_bytes() {
+        assert_eq!(decode(b""), vec![]);
+        assert_eq!(decode(&[0, 7, 10, 2, 3, 9]), vec![Op::PushLeft(7), Op::PushRight(2), Op::PopRight, Op::PushLeft(0)]);
+        let all: Vec<u8> = (0..=255).collect();
+        let ops = decode(&all);
+        assert_eq!(encode(&ops).len(), 256);
+        assert_eq!(decode(&encode(&ops)), ops);
+    }
+
+    #[test]
+    fn expand_observes_after_each_step() {
+        let ops = expand::<stack::List<i32>>(vec![Op::PushLeft(1), Op::PopRight, Op::PopLeft]);
+        // 栈不支持 pop_right 和 peek_right, 也不能反向迭代
+        assert_eq!(
+            ops,
+            vec![
+                Op::PushLeft(1),
+                Op::Len,
+                Op::PeekLeft,
+                Op::Iter,
+                Op::PopLeft,
+                Op::Len,
+                Op::PeekLeft,
+                Op::Iter,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_divergence() {
+        // 元素个数不对的栈, 第一次 push 之后的 len 就会出错
+        #[derive(Default)]
+        struct OffByOne(stack::List<i32>);
+
+        impl model::Subject for OffByOne {
+            const NAME: &'static str = "off_by_one";
+
+            fn supports(op: &Op) -> bool {
+                <stack::List<i32> as model::Subject>::supports(op)
+            }
+
+            fn apply(&mut self, op: &Op) -> model::Output {
+                match op {
+                    Op::Len if !self.0.is_empty() => model::Output::Len(self.0.len() + 1),
+                    _ => self.0.apply(op),
+                }
+            }
+        }
+
+        assert!(try_check::<OffByOne>(&[2, 4, 6, 8]).is_ok());
+        let failure = try_check::<OffByOne>(&[2, 0, 200, 0, 100, 2]).unwrap_err();
+        assert_eq!(failure.trace, vec![Op::PushLeft(0), Op::Len]);
+        assert_eq!(
+            failure.to_string(),
+            "use off_by_one\n# diverged from VecDeque after 2 ops:\npush_left 0\nlen\n# expected Len(1), got Len(2)"
+        );
+    }
+
+    // fuzz/corpus 下(包括 cargo fuzz 为每个 target 建的子目录)的每个输入都对所有链表跑一遍,
+    // 不需要安装 cargo-fuzz 也能离线回放
+    #[test]
+    fn replay_corpus() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        if let Err(e) = fs::metadata(&root) {
+            // miri 默认不允许访问文件系统, 要回放语料需要 MIRIFLAGS=-Zmiri-disable-isolation
+            if cfg!(miri) {
+                return;
+            }
+            panic!("can not read {}: {e}", root.display());
+        }
+        let mut dirs = vec![root.clone()];
+        let mut count = 0;
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let data = fs::read(&path).unwrap();
+                // miri 下只回放短的输入
+                if cfg!(miri) && data.len() > 64 {
+                    continue;
+                }
+                check_all(&data);
+                count += 1;
+            }
+        }
+        assert!(count > 0, "no inputs in {}", root.display());
+    }
+}
@@ -0,0 +1,362 @@
+//! 各个链表的 `layout()` 共用的 ASCII 内存布局图
+//!
+//! 画法和 `src/bin` 里注释手画的一样: 栈上的链表头画成 `[name]`, 堆上的节点画成 `(elem, ptr)`,
+//! 最后一个节点画成 `(elem, null)`:
+//! ```text
+//! [list1] -> (A, ptr) -> (B, ptr) -> (C, ptr) -> (D, null)
+//!                         ^
+//! [list2] ----------------+
+//!                         |
+//! [list3] -> (X, ptr) ----+
+//! ```
+//! 每个链表占一行, 节点画在第一个经过它的链表那一行上,
+//! 之后的链表走到已经画过的节点时, 用 `+` 和 `^` 把箭头接到那个节点下面。
+//! 同一列的节点上下对齐, 列号是从链表头出发到这个节点的最长路径;
+//! 如果往上的竖线会压到中间某一行的节点, 就把目标节点继续往右挪, 让竖线走空着的列。
+//! 竖线和别的横线交叉时直接穿过去画成 `|`, `+` 只出现在接入的地方
+
+use std::collections::HashMap;
+use std::fmt;
+
+pub(crate) struct Layout {
+    // 每个节点画出来的文字
+    texts: Vec<String>,
+    ids: HashMap<*const (), usize>,
+    lists: Vec<Row>,
+}
+
+struct Row {
+    label: String,
+    // 这一行新画的节点
+    fresh: Vec<usize>,
+    // 最后接到的已经画过的节点
+    join: Option<usize>,
+}
+
+impl Layout {
+    pub(crate) fn new() -> Self {
+        Layout {
+            texts: Vec::new(),
+            ids: HashMap::new(),
+            lists: Vec::new(),
+        }
+    }
+
+    // 开始画新的一行
+    pub(crate) fn list(&mut self, label: &str) {
+        self.lists.push(Row {
+            label: label.to_string(),
+            fresh: Vec::new(),
+            join: None,
+        });
+    }
+
+    // 在当前行加一个节点, 返回 false 表示这个节点之前已经画过, 这一行到此为止
+    pub(crate) fn node<P, E>(&mut self, ptr: *const P, elem: &E, has_next: bool) -> bool
+    where
+        E: fmt::Display + ?Sized,
+    {
+        let row = self.lists.last_mut().expect("call list() first");
+        let ptr = ptr.cast::<()>();
+        if let Some(&id) = self.ids.get(&ptr) {
+            row.join = Some(id);
+            return false;
+        }
+        let id = self.texts.len();
+        self.ids.insert(ptr, id);
+        let next = if has_next { "ptr" } else { "null" };
+        self.texts.push(format!("({elem}, {next})"));
+        row.fresh.push(id);
+        true
+    }
+
+    pub(crate) fn render(&self) -> String {
+        if self.lists.is_empty() {
+            return String::new();
+        }
+        let n = self.texts.len();
+
+        // 节点的 next 和它被画在哪一行
+        let mut next = vec![None; n];
+        let mut owner = vec![0; n];
+        for (r, row) in self.lists.iter().enumerate() {
+            for (i, &id) in row.fresh.iter().enumerate() {
+                next[id] = row.fresh.get(i + 1).copied().or(row.join);
+                owner[id] = r;
+            }
+        }
+
+        // 竖线从接入的那一行一直往上画到目标节点, 不能压到中间各行的节点(或者 "-> null"),
+        // 否则看起来就像指向了那个节点。碰到这种情况就把目标节点往右挪一列, 然后重新排列
+        let mut min_col = vec![1; n];
+        let col = loop {
+            let col = self.columns(&next, &min_col);
+            let blocked = self.lists.iter().enumerate().find_map(|(r, row)| {
+                let id = row.join?;
+                let c = col[id];
+                let crossed = self.lists[owner[id] + 1..r].iter().any(|mid| {
+                    mid.fresh.iter().any(|&f| col[f] == c) || (mid.fresh.is_empty() && mid.join.is_none() && c == 1)
+                });
+                crossed.then_some(id)
+            });
+            match blocked {
+                Some(id) => min_col[id] = col[id] + 1,
+                None => break col,
+            }
+        };
+
+        // 每一列的起始位置, 列之间留出 " -> " 的宽度
+        let cols = col.iter().copied().max().unwrap_or(0) + 1;
+        let mut width = vec![0; cols];
+        for row in &self.lists {
+            width[0] = width[0].max(row.label.chars().count() + 2);
+            // 空链表的 "-> null" 里的 null 占第一列的位置
+            if row.fresh.is_empty() && row.join.is_none() && cols > 1 {
+                width[1] = width[1].max("null".len());
+            }
+        }
+        for id in 0..n {
+            width[col[id]] = width[col[id]].max(self.texts[id].chars().count());
+        }
+        let mut x = vec![0; cols];
+        for c in 1..cols {
+            x[c] = x[c - 1] + width[c - 1] + 4;
+        }
+
+        // 链表占偶数行, 奇数行留给竖线
+        let mut grid = Grid::new(self.lists.len() * 2 - 1);
+        for (r, row) in self.lists.iter().enumerate() {
+            let line = r * 2;
+            let mut end = grid.text(line, 0, &format!("[{}]", row.label));
+            for &id in &row.fresh {
+                grid.arrow(line, end, x[col[id]]);
+                end = grid.text(line, x[col[id]], &self.texts[id]);
+            }
+            match row.join {
+                Some(id) => {
+                    // 连到目标节点第一个字符的正下方
+                    let target = x[col[id]] + 1;
+                    grid.horizontal(line, end, target);
+                    grid.set(line, target, '+');
+                    let top = owner[id] * 2;
+                    grid.set(top + 1, target, '^');
+                    for l in top + 2..line {
+                        grid.vertical(l, target);
+                    }
+                }
+                None if row.fresh.is_empty() => {
+                    grid.text(line, end, " -> null");
+                }
+                None => {}
+            }
+        }
+        grid.finish()
+    }
+
+    // 列号: 沿着每个链表走到底, 后面的节点至少比前面的大一列, 也不小于 min_col
+    // 只会往尾部方向变大, 所以每个链表走一遍就够了
+    fn columns(&self, next: &[Option<usize>], min_col: &[usize]) -> Vec<usize> {
+        let mut col = min_col.to_vec();
+        for row in &self.lists {
+            let mut cur = row.fresh.first().copied().or(row.join);
+            let mut c = 1;
+            while let Some(id) = cur {
+                col[id] = col[id].max(c);
+                c = col[id] + 1;
+                cur = next[id];
+            }
+        }
+        col
+    }
+}
+
+struct Grid {
+    lines: Vec<Vec<char>>,
+}
+
+impl Grid {
+    fn new(lines: usize) -> Self {
+        Grid {
+            lines: vec![Vec::new(); lines],
+        }
+    }
+
+    fn set(&mut self, line: usize, x: usize, c: char) {
+        let line = &mut self.lines[line];
+        if line.len() <= x {
+            line.resize(x + 1, ' ');
+        }
+        line[x] = c;
+    }
+
+    fn get(&self, line: usize, x: usize) -> char {
+        self.lines[line].get(x).copied().unwrap_or(' ')
+    }
+
+    // 写一段文字, 返回结束的位置
+    fn text(&mut self, line: usize, x: usize, s: &str) -> usize {
+        let mut end = x;
+        for c in s.chars() {
+            self.set(line, end, c);
+            end += 1;
+        }
+        end
+    }
+
+    // 从 from 画到 to 前面: " --> "
+    fn arrow(&mut self, line: usize, from: usize, to: usize) {
+        self.horizontal(line, from, to - 1);
+        self.set(line, to - 2, '>');
+    }
+
+    // " ----", 画到 to 前面为止
+    fn horizontal(&mut self, line: usize, from: usize, to: usize) {
+        for x in from + 1..to {
+            self.set(line, x, '-');
+        }
+    }
+
+    // 竖线直接穿过别的横线, 只有接入的地方才画 '+'
+    // render 排列节点时已经保证竖线不会压到节点的文字
+    fn vertical(&mut self, line: usize, x: usize) {
+        match self.get(line, x) {
+            ' ' | '-' => self.set(line, x, '|'),
+            '|' | '+' => {}
+            c => unreachable!("vertical line runs into {c:?}"),
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        for line in self.lines {
+            let line: String = line.into_iter().collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{persistent, persistent_sync, stack};
+
+    #[test]
+    fn stack_layout() {
+        // list1.rs / list2.rs 注释里画的布局
+        let list = stack::List::from([1, 2]);
+        assert_eq!(stack::List::layout(&[("ptr", &list)]), "[ptr] -> (1, ptr) -> (2, null)\n");
+        assert_eq!(stack::List::<i32>::layout(&[("ptr", &stack::List::new())]), "[ptr] -> null\n");
+        assert_eq!(stack::List::<i32>::layout(&[]), "");
+
+        let other = stack::List::from([10]);
+        assert_eq!(
+            stack::List::layout(&[("a", &list), ("bb", &other)]),
+            "[a] --> (1, ptr) ---> (2, null)\n\n[bb] -> (10, null)\n"
+        );
+    }
+
+    #[test]
+    fn persistent_shared_tail() {
+        // list8.rs 注释里的结构: B 被三个链表共享
+        let list2 = persistent::List::new().push_left("D").push_left("C").push_left("B");
+        let list1 = list2.push_left("A");
+        let list3 = list2.push_left("X");
+        assert_eq!(
+            persistent::List::layout(&[("list1", &list1), ("list2", &list2), ("list3", &list3)]),
+            "\
+[list1] -> (A, ptr) -> (B, ptr) -> (C, ptr) -> (D, null)
+                        ^
+[list2] ----------------+
+                        |
+[list3] -> (X, ptr) ----+
+"
+        );
+    }
+
+    #[test]
+    fn persistent_push_history() {
+        // list8.rs 里 push 三次的例子, 每个版本都是上一个版本加一个头
+        let list1 = persistent::List::new().push_left("A");
+        let list2 = list1.push_left("B");
+        let list3 = list2.push_left("C");
+        assert_eq!(
+            persistent::List::layout(&[("list3", &list3), ("list2", &list2), ("list1", &list1)]),
+            "\
+[list3] -> (C, ptr) -> (B, ptr) -> (A, null)
+                        ^           ^
+[list2] ----------------+           |
+                                    |
+[list1] ----------------------------+
+"
+        );
+    }
+
+    #[test]
+    fn later_list_pushes_columns_right() {
+        // 后面的链表更长, 共享的节点整体右移, 前面的箭头也跟着拉长
+        let shared = persistent_sync::List::new().push_left(0);
+        let short = shared.push_left(1);
+        let long = shared.push_left(2).push_left(3).push_left(4);
+        assert_eq!(
+            persistent_sync::List::layout(&[("short", &short), ("long", &long)]),
+            "\
+[short] -> (1, ptr) -------------------------> (0, null)
+                                                ^
+[long] --> (4, ptr) -> (3, ptr) -> (2, ptr) ----+
+"
+        );
+    }
+
+    #[test]
+    fn crossing_lines() {
+        let base = persistent::List::new().push_left(0);
+        let a = base.push_left(1);
+        let b = persistent::List::new().push_left(9).push_left(8);
+        let c = base.push_left(2);
+        let out = persistent::List::layout(&[("a", &a), ("b", &b), ("c", &c)]);
+        // 直着往上画会压到 b 的 (9, null), 像是 c 指向了 9, 所以 0 挪到右边单独一列
+        assert_eq!(
+            out,
+            "\
+[a] -> (1, ptr) --------------> (0, null)
+                                 ^
+[b] -> (8, ptr) -> (9, null)     |
+                                 |
+[c] -> (2, ptr) -----------------+
+"
+        );
+
+        // 接不同节点的连线交叉时竖线直接穿过横线, 只有接入的地方才是 '+'
+        let shared = persistent::List::new().push_left(3);
+        let a = persistent::List::new().push_left(1).push_left(2);
+        let b = a.push_left(4);
+        let out = persistent::List::layout(&[("a", &shared), ("b", &a), ("c", &shared), ("d", &b)]);
+        assert_eq!(
+            out,
+            "\
+[a] -> (3, null)
+        ^
+[b] ----|---------> (2, ptr) -> (1, null)
+        |            ^
+[c] ----+            |
+                     |
+[d] -> (4, ptr) -----+
+"
+        );
+
+        // 空链表的 "-> null" 也不能被竖线穿过
+        let empty = persistent::List::new();
+        let out = persistent::List::layout(&[("a", &a), ("b", &empty), ("c", &a)]);
+        assert_eq!(
+            out,
+            "\
+[a] ---------> (2, ptr) -> (1, null)
+                ^
+[b] -> null     |
+                |
+[c] ------------+
+"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! rust循序渐进写链表
+//!
+//! `src/bin` 下的 list1 ~ list9 记录了链表一步步演化的过程,
+//! 这里把其中可以直接使用的几种链表整理成库:
+//!
+//! - [`stack`]: 基于 `Option<Box<Node<T>>>` 的单向链表栈 (list6)
+//! - [`persistent`]: 基于 `Rc` 的不可变(持久化)链表栈 (list8)
+//! - [`persistent_sync`]: 基于 `Arc` 的不可变链表栈, 可以跨线程共享
+//! - [`shared`]: 上面两种不可变链表共用的实现, 对引用计数指针泛型
+//! - [`persistent_queue`]: 用两个不可变链表栈拼成的 FIFO 队列, 旧版本依然可用
+//! - [`deque`]: 基于 `Rc<RefCell<Node<T>>>` 的双向链表 (list9)
+//! - [`queue`]: 带 tail 裸指针的单向链表队列
+//! - [`unsafe_deque`]: 基于 `NonNull` 的双向链表, 用来替代 [`deque`]
+//!
+//! 它们共同实现了 [`traits`] 里的 [`Stack`](traits::Stack)、[`Queue`](traits::Queue)、
+//! [`Deque`](traits::Deque)、[`PersistentStack`](traits::PersistentStack)、
+//! [`PersistentQueue`](traits::PersistentQueue) 等 trait
+//!
+//! 打开 `serde` feature 后, [`stack`]、[`persistent`]、[`persistent_sync`]、[`deque`]
+//! 的链表可以按普通序列的格式序列化和反序列化
+//!
+//! 每种链表都有 `to_dot()`, 把一个或多个链表的节点、指针和引用计数导出成 Graphviz 图,
+//! 方便查看节点之间是怎么共享的。
+//! [`stack`]、[`persistent`]、[`persistent_sync`] 的 `layout()` 则画出和 `src/bin`
+//! 注释里一样的 ASCII 布局图, 例如 `[ptr] -> (1, ptr) -> (2, null)`
+//!
+//! [`model`] 用 `VecDeque` 做参照, 对所有链表跑随机的差分测试,
+//! [`fuzz`] 则把任意字节串当成操作序列做同样的检查, 供 `fuzz/` 下的 cargo-fuzz target 调用
+
+pub mod deque;
+mod dot;
+pub mod fuzz;
+mod layout;
+pub mod model;
+pub mod persistent;
+pub mod persistent_queue;
+pub mod persistent_sync;
+pub mod queue;
+mod range;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod shared;
+mod sort;
+pub mod stack;
+mod teardown;
+#[cfg(test)]
+mod testkit;
+pub mod traits;
+pub mod unsafe_deque;
+#[cfg(feature = "serde")]
+mod versions;
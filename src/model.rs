@@ -0,0 +1,645 @@
+//! 拿 `VecDeque` 当参照, 对各种链表做随机的差分测试
+//!
+//! 用固定的种子生成一长串随机操作, 同时作用在链表和 `VecDeque` 上, 每一步都比较结果。
+//! 结果不一致(或者链表 panic)时, 把操作序列缩减到仍然出错的最短序列再报告, 例如
+//! ```text
+//! use stack
+//! # diverged from VecDeque (seed 7) after 2 ops:
+//! push_left 0
+//! pop_left
+//! # expected Elem(Some(0)), got Elem(None)
+//! ```
+//! 报告本身就是一段 `listctl` 脚本, 说明写在 `#` 开头的注释里, 可以直接粘贴过去复现
+//!
+//! ```
+//! use rust_linklist::{model, stack};
+//!
+//! if let Err(failure) = model::check::<stack::List<i32>>(42, 1000) {
+//!     panic!("{failure}");
+//! }
+//! ```
+
+use crate::{deque, persistent, persistent_queue, persistent_sync, queue, stack, unsafe_deque};
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// 一步操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    PushLeft(i32),
+    PushRight(i32),
+    PopLeft,
+    PopRight,
+    PeekLeft,
+    PeekRight,
+    /// 从左到右迭代
+    Iter,
+    /// 从右到左迭代
+    IterRev,
+    Len,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::PushLeft(elem) => write!(f, "push_left {elem}"),
+            Op::PushRight(elem) => write!(f, "push_right {elem}"),
+            Op::PopLeft => f.write_str("pop_left"),
+            Op::PopRight => f.write_str("pop_right"),
+            Op::PeekLeft => f.write_str("peek_left"),
+            Op::PeekRight => f.write_str("peek_right"),
+            Op::Iter => f.write_str("iter"),
+            Op::IterRev => f.write_str("iter_rev"),
+            Op::Len => f.write_str("len"),
+        }
+    }
+}
+
+/// 一步操作的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    /// push 没有返回值
+    Unit,
+    /// pop 和 peek 的结果
+    Elem(Option<i32>),
+    /// 迭代得到的所有元素
+    Elems(Vec<i32>),
+    Len(usize),
+    /// 链表 panic 了, 里面是 panic 的信息
+    Panic(String),
+}
+
+/// 被测试的链表, 每种链表实现一次
+///
+/// 不可变链表在 `apply` 里用新版本替换掉自己
+pub trait Subject: Default {
+    /// 报告里显示的名字
+    const NAME: &'static str;
+
+    /// 这种链表支持哪些操作, 生成操作时跳过不支持的
+    fn supports(op: &Op) -> bool;
+
+    fn apply(&mut self, op: &Op) -> Output;
+}
+
+/// 参照模型, 定义每个操作应有的结果
+pub fn apply_model(model: &mut VecDeque<i32>, op: &Op) -> Output {
+    match *op {
+        Op::PushLeft(elem) => {
+            model.push_front(elem);
+            Output::Unit
+        }
+        Op::PushRight(elem) => {
+            model.push_back(elem);
+            Output::Unit
+        }
+        Op::PopLeft => Output::Elem(model.pop_front()),
+        Op::PopRight => Output::Elem(model.pop_back()),
+        Op::PeekLeft => Output::Elem(model.front().copied()),
+        Op::PeekRight => Output::Elem(model.back().copied()),
+        Op::Iter => Output::Elems(model.iter().copied().collect()),
+        Op::IterRev => Output::Elems(model.iter().rev().copied().collect()),
+        Op::Len => Output::Len(model.len()),
+    }
+}
+
+/// xorshift64*, 同一个种子总是生成同样的序列
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        const MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+        // 状态是 0 时 xorshift 只会一直生成 0, 唯一会得到 0 的种子(也就是 MIX 本身)
+        // 换成固定的非零状态, 和种子 0 生成同样的序列
+        match seed ^ MIX {
+            0 => Rng(MIX),
+            state => Rng(state),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// `0..n` 里的随机数, `n` 不能是 0
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// 生成 `len` 步 `S` 支持的随机操作
+///
+/// push 比 pop 多一些, 链表会慢慢变长; 迭代的代价是 O(n), 所以少生成一些
+pub fn generate<S: Subject>(seed: u64, len: usize) -> Vec<Op> {
+    let mut rng = Rng::new(seed);
+    let mut ops = Vec::with_capacity(len);
+    while ops.len() < len {
+        let op = match rng.below(20) {
+            0..=4 => Op::PushLeft(rng.below(1000) as i32),
+            5..=9 => Op::PushRight(rng.below(1000) as i32),
+            10..=12 => Op::PopLeft,
+            13..=15 => Op::PopRight,
+            16 => Op::PeekLeft,
+            17 => Op::PeekRight,
+            18 => Op::Len,
+            _ if rng.below(2) == 0 => Op::Iter,
+            _ => Op::IterRev,
+        };
+        if S::supports(&op) {
+            ops.push(op);
+        }
+    }
+    ops
+}
+
+/// 在第几步出现了不一致
+///
+/// 链表在所有操作都结束之后, 析构时才 panic 的话, `step` 等于操作的个数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub step: usize,
+    pub expected: Output,
+    pub actual: Output,
+}
+
+/// 依次执行 `ops`, 在第一处不一致的地方停下
+pub fn run<S: Subject>(ops: &[Op]) -> Result<(), Divergence> {
+    let mut model = VecDeque::new();
+    let mut step = 0;
+    let mut expected = Output::Unit;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut subject = S::default();
+        for op in ops {
+            expected = apply_model(&mut model, op);
+            let actual = subject.apply(op);
+            if actual != expected {
+                return Err(actual);
+            }
+            step += 1;
+        }
+        expected = Output::Unit;
+        drop(subject);
+        Ok(())
+    }));
+    let actual = match result {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(actual)) => actual,
+        Err(payload) => Output::Panic(panic_message(payload)),
+    };
+    Err(Divergence { step, expected, actual })
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic>".to_string()
+    }
+}
+
+/// 把出错的操作序列缩减到仍然出错的最短序列
+///
+/// 先去掉出错之后的操作, 再不断尝试删掉一段操作、把 push 的元素换成 0,
+/// 直到再也删不动为止。`ops` 必须是会出错的序列
+pub fn shrink<S: Subject>(mut ops: Vec<Op>) -> (Vec<Op>, Divergence) {
+    let mut divergence = run::<S>(&ops).expect_err("shrink() needs a failing trace");
+    ops.truncate(divergence.step + 1);
+
+    loop {
+        let mut progress = false;
+
+        // 从大到小删掉连续的一段
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start + chunk <= ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..start + chunk);
+                match run::<S>(&candidate) {
+                    Err(d) => {
+                        candidate.truncate(d.step + 1);
+                        ops = candidate;
+                        divergence = d;
+                        progress = true;
+                    }
+                    Ok(()) => start += 1,
+                }
+            }
+            chunk /= 2;
+        }
+
+        // 元素越小越好读
+        for i in 0..ops.len() {
+            let mut candidate = ops.clone();
+            match &mut candidate[i] {
+                Op::PushLeft(elem) | Op::PushRight(elem) if *elem != 0 => *elem = 0,
+                _ => continue,
+            }
+            if let Err(d) = run::<S>(&candidate) {
+                ops = candidate;
+                divergence = d;
+                progress = true;
+            }
+        }
+
+        if !progress {
+            return (ops, divergence);
+        }
+    }
+}
+
+/// 差分测试失败的报告, `Display` 输出缩减后的操作序列
+#[derive(Debug)]
+pub struct Failure {
+    pub name: &'static str,
+    /// 随机生成的操作才有种子, 模糊测试的输入没有
+    pub seed: Option<u64>,
+    pub trace: Vec<Op>,
+    pub divergence: Divergence,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "use {}", self.name)?;
+        write!(f, "# diverged from VecDeque")?;
+        if let Some(seed) = self.seed {
+            write!(f, " (seed {seed})")?;
+        }
+        writeln!(f, " after {} ops:", self.trace.len())?;
+        for op in &self.trace {
+            writeln!(f, "{op}")?;
+        }
+        write!(f, "# expected {:?}, got {:?}", self.divergence.expected, self.divergence.actual)
+    }
+}
+
+/// 用 `seed` 生成 `len` 步操作来测试 `S`, 出错时返回缩减后的报告
+pub fn check<S: Subject>(seed: u64, len: usize) -> Result<(), Box<Failure>> {
+    let ops = generate::<S>(seed, len);
+    if run::<S>(&ops).is_ok() {
+        return Ok(());
+    }
+    let (trace, divergence) = shrink::<S>(ops);
+    Err(Box::new(Failure {
+        name: S::NAME,
+        seed: Some(seed),
+        trace,
+        divergence,
+    }))
+}
+
+impl Subject for stack::List<i32> {
+    const NAME: &'static str = "stack";
+
+    fn supports(op: &Op) -> bool {
+        matches!(op, Op::PushLeft(_) | Op::PopLeft | Op::PeekLeft | Op::Iter | Op::Len)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                self.push_left(elem);
+                Output::Unit
+            }
+            Op::PopLeft => Output::Elem(self.pop_left()),
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+impl Subject for queue::List<i32> {
+    const NAME: &'static str = "queue";
+
+    fn supports(op: &Op) -> bool {
+        !matches!(op, Op::PopRight | Op::IterRev)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                self.push_left(elem);
+                Output::Unit
+            }
+            Op::PushRight(elem) => {
+                self.push_right(elem);
+                Output::Unit
+            }
+            Op::PopLeft => Output::Elem(self.pop_left()),
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::PeekRight => Output::Elem(self.peek_right().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+impl Subject for deque::List<i32> {
+    const NAME: &'static str = "deque";
+
+    fn supports(_op: &Op) -> bool {
+        true
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                self.push_left(elem);
+                Output::Unit
+            }
+            Op::PushRight(elem) => {
+                self.push_right(elem);
+                Output::Unit
+            }
+            Op::PopLeft => Output::Elem(self.pop_left()),
+            Op::PopRight => Output::Elem(self.pop_right()),
+            Op::PeekLeft => Output::Elem(self.peek_left().map(|elem| *elem)),
+            Op::PeekRight => Output::Elem(self.peek_right().map(|elem| *elem)),
+            Op::Iter => Output::Elems(self.iter().map(|elem| *elem.borrow()).collect()),
+            Op::IterRev => Output::Elems(self.iter().rev().map(|elem| *elem.borrow()).collect()),
+            Op::Len => Output::Len(self.len()),
+        }
+    }
+}
+
+impl Subject for unsafe_deque::List<i32> {
+    const NAME: &'static str = "unsafe_deque";
+
+    fn supports(_op: &Op) -> bool {
+        true
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                self.push_left(elem);
+                Output::Unit
+            }
+            Op::PushRight(elem) => {
+                self.push_right(elem);
+                Output::Unit
+            }
+            Op::PopLeft => Output::Elem(self.pop_left()),
+            Op::PopRight => Output::Elem(self.pop_right()),
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::PeekRight => Output::Elem(self.peek_right().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::IterRev => Output::Elems(self.iter().rev().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+        }
+    }
+}
+
+impl Subject for persistent::List<i32> {
+    const NAME: &'static str = "persistent";
+
+    fn supports(op: &Op) -> bool {
+        matches!(op, Op::PushLeft(_) | Op::PopLeft | Op::PeekLeft | Op::Iter | Op::Len)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                *self = self.push_left(elem);
+                Output::Unit
+            }
+            Op::PopLeft => {
+                let head = self.peek_left().copied();
+                *self = self.pop_left();
+                Output::Elem(head)
+            }
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+impl Subject for persistent_sync::List<i32> {
+    const NAME: &'static str = "persistent_sync";
+
+    fn supports(op: &Op) -> bool {
+        matches!(op, Op::PushLeft(_) | Op::PopLeft | Op::PeekLeft | Op::Iter | Op::Len)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushLeft(elem) => {
+                *self = self.push_left(elem);
+                Output::Unit
+            }
+            Op::PopLeft => {
+                let head = self.peek_left().copied();
+                *self = self.pop_left();
+                Output::Elem(head)
+            }
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+impl Subject for persistent_queue::Queue<i32> {
+    const NAME: &'static str = "persistent_queue";
+
+    fn supports(op: &Op) -> bool {
+        matches!(op, Op::PushRight(_) | Op::PopLeft | Op::PeekLeft | Op::Iter | Op::Len)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushRight(elem) => {
+                *self = self.push_right(elem);
+                Output::Unit
+            }
+            Op::PopLeft => {
+                let head = self.peek_left().copied();
+                *self = self.pop_left();
+                Output::Elem(head)
+            }
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+impl Subject for persistent_queue::RealTimeQueue<i32> {
+    const NAME: &'static str = "realtime_queue";
+
+    fn supports(op: &Op) -> bool {
+        matches!(op, Op::PushRight(_) | Op::PopLeft | Op::PeekLeft | Op::Iter | Op::Len)
+    }
+
+    fn apply(&mut self, op: &Op) -> Output {
+        match *op {
+            Op::PushRight(elem) => {
+                *self = self.push_right(elem);
+                Output::Unit
+            }
+            Op::PopLeft => {
+                let head = self.peek_left().copied();
+                *self = self.pop_left();
+                Output::Elem(head)
+            }
+            Op::PeekLeft => Output::Elem(self.peek_left().copied()),
+            Op::Iter => Output::Elems(self.iter().copied().collect()),
+            Op::Len => Output::Len(self.len()),
+            _ => unreachable!("unsupported op {op}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, generate, run, shrink, Op, Output, Rng, Subject};
+    use crate::{deque, persistent, persistent_queue, persistent_sync, queue, stack, unsafe_deque};
+
+    // miri 下跑得很慢, 缩短序列
+    const LEN: usize = if cfg!(miri) { 200 } else { 10_000 };
+    const SEEDS: u64 = if cfg!(miri) { 1 } else { 8 };
+
+    fn check_seeds<S: Subject>() {
+        for seed in 0..SEEDS {
+            if let Err(failure) = check::<S>(seed, LEN) {
+                panic!("{failure}");
+            }
+        }
+    }
+
+    #[test]
+    fn all_impls_match_vec_deque() {
+        check_seeds::<stack::List<i32>>();
+        check_seeds::<queue::List<i32>>();
+        check_seeds::<deque::List<i32>>();
+        check_seeds::<unsafe_deque::List<i32>>();
+        check_seeds::<persistent::List<i32>>();
+        check_seeds::<persistent_sync::List<i32>>();
+        check_seeds::<persistent_queue::Queue<i32>>();
+        check_seeds::<persistent_queue::RealTimeQueue<i32>>();
+    }
+
+    #[test]
+    fn zero_state_seed() {
+        // 这个种子以前会让状态变成 0, 生成的全是 push_left, 队列不支持, generate 就停不下来
+        let seed = 0x9E37_79B9_7F4A_7C15;
+        let mut rng = Rng::new(seed);
+        assert!((0..10).any(|_| rng.next_u64() != 0));
+        assert_eq!(generate::<persistent_queue::Queue<i32>>(seed, 50), generate::<persistent_queue::Queue<i32>>(0, 50));
+        assert!(check::<persistent_queue::Queue<i32>>(seed, 50).is_ok());
+    }
+
+    #[test]
+    fn generate_is_deterministic() {
+        let a = generate::<deque::List<i32>>(3, 100);
+        assert_eq!(a, generate::<deque::List<i32>>(3, 100));
+        assert_ne!(a, generate::<deque::List<i32>>(4, 100));
+        // 只生成支持的操作
+        assert!(generate::<stack::List<i32>>(3, 1000)
+            .iter()
+            .all(<stack::List<i32> as Subject>::supports));
+    }
+
+    // 故意写错的栈: 长度到 3 之后 pop 会把元素弄丢
+    #[derive(Default)]
+    struct Buggy(Vec<i32>);
+
+    impl Subject for Buggy {
+        const NAME: &'static str = "buggy";
+
+        fn supports(op: &Op) -> bool {
+            <stack::List<i32> as Subject>::supports(op)
+        }
+
+        fn apply(&mut self, op: &Op) -> Output {
+            match *op {
+                Op::PushLeft(elem) => {
+                    self.0.push(elem);
+                    Output::Unit
+                }
+                Op::PopLeft => {
+                    let elem = self.0.pop();
+                    if self.0.len() >= 2 {
+                        return Output::Elem(None);
+                    }
+                    Output::Elem(elem)
+                }
+                Op::PeekLeft => Output::Elem(self.0.last().copied()),
+                Op::Iter => Output::Elems(self.0.iter().rev().copied().collect()),
+                Op::Len => Output::Len(self.0.len()),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks_to_smallest_trace() {
+        let failure = check::<Buggy>(1, 1000).unwrap_err();
+        assert_eq!(
+            failure.trace,
+            vec![Op::PushLeft(0), Op::PushLeft(0), Op::PushLeft(0), Op::PopLeft]
+        );
+        assert_eq!(failure.divergence.step, 3);
+        assert_eq!(failure.divergence.expected, Output::Elem(Some(0)));
+        assert_eq!(failure.divergence.actual, Output::Elem(None));
+        // 换个种子, 缩减的结果长度一样
+        assert_eq!(check::<Buggy>(2, 1000).unwrap_err().trace.len(), 4);
+    }
+
+    // push 到第二个元素就 panic
+    #[derive(Default)]
+    struct Panicky(usize);
+
+    impl Subject for Panicky {
+        const NAME: &'static str = "panicky";
+
+        fn supports(op: &Op) -> bool {
+            matches!(op, Op::PushLeft(_) | Op::Len)
+        }
+
+        fn apply(&mut self, op: &Op) -> Output {
+            match op {
+                Op::PushLeft(_) => {
+                    self.0 += 1;
+                    assert!(self.0 < 2, "too many elems");
+                    Output::Unit
+                }
+                _ => Output::Len(self.0),
+            }
+        }
+    }
+
+    #[test]
+    fn panics_are_divergences() {
+        let ops = generate::<Panicky>(0, 50);
+        let d = run::<Panicky>(&ops).unwrap_err();
+        assert_eq!(d.actual, Output::Panic("too many elems".to_string()));
+
+        let (trace, _) = shrink::<Panicky>(ops);
+        assert_eq!(trace, vec![Op::PushLeft(0), Op::PushLeft(0)]);
+        let report = check::<Panicky>(0, 50).unwrap_err().to_string();
+        assert_eq!(
+            report,
+            "use panicky\n\
+             # diverged from VecDeque (seed 0) after 2 ops:\n\
+             push_left 0\n\
+             push_left 0\n\
+             # expected Unit, got Panic(\"too many elems\")"
+        );
+    }
+}
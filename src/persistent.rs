@@ -0,0 +1,259 @@
+//! 基于 `Rc` 的不可变(持久化)链表栈 (对应 `src/bin/list8.rs`)
+//!
+//! 节点一旦创建就不会再改变, 所以多个链表可以共享同一段尾部:
+//! ```text
+//! list1 -> A ---+
+//!               |
+//!               v
+//! list2 ------> B -> C -> D
+//!               ^
+//!               |
+//! list3 -> X ---+
+//! ```
+
+use crate::shared::{self, SharedPointer};
+#[cfg(feature = "serde")]
+use crate::versions::{self, Nodes};
+use std::rc::Rc;
+
+/// 用 `Rc` 管理节点, 只能在一个线程里使用
+pub enum RcPointer {}
+
+impl SharedPointer for RcPointer {
+    type Ptr<N> = Rc<N>;
+
+    fn new<N>(node: N) -> Rc<N> {
+        Rc::new(node)
+    }
+
+    fn strong_count<N>(ptr: &Rc<N>) -> usize {
+        Rc::strong_count(ptr)
+    }
+
+    fn into_inner<N>(ptr: Rc<N>) -> Option<N> {
+        // 判断当前的 Rc 是否只有一个强引用，若是，则返回 Rc 持有的值，否则返回一个错误
+        Rc::try_unwrap(ptr).ok()
+    }
+}
+
+/// 不可变链表, 每次 push/pop 都会返回一个新的版本, 旧版本依然可用
+pub type List<T> = shared::List<T, RcPointer>;
+
+/// 按不可变引用迭代, 由 [`List::iter`](shared::List::iter) 创建
+pub type Iter<'a, T> = shared::Iter<'a, T, RcPointer>;
+
+/// 一批可能共享尾部的链表版本, 序列化时每个节点只写一次, 需要打开 `serde` feature
+///
+/// 序列化成 `(nodes, heads)`:
+/// - `nodes` 是 `(elem, next)` 的序列, `next` 是下一个节点在 `nodes` 里的下标, 没有下一个节点时为空
+/// - `heads` 依次是每个版本头节点的下标, 空链表为空
+///
+/// 下标从尾部开始分配, 每个节点的 `next` 都比自己小, 读取时从前往后建节点就行, 不需要递归。
+/// 读回来的版本之间共享的节点和写出去时一样, 可以用 [`List::ptr_eq`](shared::List::ptr_eq) 检查
+#[cfg(feature = "serde")]
+pub struct SharedVersions<T>(pub Vec<List<T>>);
+
+#[cfg(feature = "serde")]
+impl<T> SharedVersions<T> {
+    pub(crate) fn encode(&self) -> (Nodes<&T>, Vec<Option<usize>>) {
+        versions::encode(&self.0)
+    }
+
+    pub(crate) fn decode(nodes: Nodes<T>, heads: Vec<Option<usize>>) -> Result<Self, String> {
+        versions::decode(nodes, heads).map(SharedVersions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn test_iter() {
+        let list = List::new();
+        let list = list.push_left(1).push_left(2).push_left(3);
+        let mut it = list.iter();
+        assert_eq!(it.next(), Some(&3));
+        assert_eq!(it.next(), Some(&2));
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn len() {
+        let list1 = List::new().push_left(1).push_left(2);
+        let list2 = list1.push_left(3);
+        let list3 = list1.pop_left().push_left(4).push_left(5).push_left(6);
+        assert_eq!(List::<i32>::new().len(), 0);
+        assert!(List::<i32>::new().is_empty());
+        assert_eq!(list1.len(), 2);
+        assert_eq!(list2.len(), 3);
+        assert_eq!(list3.len(), 4);
+        assert!(!list3.is_empty());
+
+        let mut iter = list3.iter();
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        drop(list1);
+        assert_eq!(list2.pop_left().len(), 2);
+    }
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.peek_left(), None);
+
+        let list = list.push_left(1).push_left(2).push_left(3);
+        assert_eq!(list.peek_left(), Some(&3));
+
+        let list = list.pop_left();
+        assert_eq!(list.peek_left(), Some(&2));
+
+        let list = list.pop_left();
+        assert_eq!(list.peek_left(), Some(&1));
+
+        let list = list.pop_left();
+        assert_eq!(list.peek_left(), None);
+
+        // Make sure empty tail works
+        let list = list.pop_left();
+        assert_eq!(list.peek_left(), None);
+    }
+
+    #[test]
+    fn shared_tail() {
+        let list1 = List::new().push_left(1);
+        let list2 = list1.push_left(2);
+        let list3 = list1.push_left(3);
+
+        // 旧版本不受新版本影响
+        assert_eq!(list1.iter().collect::<Vec<_>>(), vec![&1]);
+        assert_eq!(list2.iter().collect::<Vec<_>>(), vec![&2, &1]);
+        assert_eq!(list3.iter().collect::<Vec<_>>(), vec![&3, &1]);
+
+        // 共享的是同一个节点, 而不是值相等的两个节点
+        assert!(list2.pop_left().ptr_eq(&list3.pop_left()));
+        assert!(!list2.ptr_eq(&list3));
+        assert!(!List::new().push_left(1).ptr_eq(&list1));
+        assert!(List::<i32>::new().ptr_eq(&list1.pop_left()));
+
+        drop(list1);
+        assert_eq!(list2.iter().collect::<Vec<_>>(), vec![&2, &1]);
+    }
+
+    #[test]
+    fn fmt() {
+        let list = List::new().push_left(1).push_left(2).push_left(3);
+        let tail = list.pop_left();
+        assert_eq!(format!("{:?}", list), "[3, 2, 1]");
+        assert_eq!(format!("{:?}", tail), "[2, 1]");
+        assert_eq!(format!("{:#?}", tail), "[\n    2,\n    1,\n]");
+        assert_eq!(list.to_string(), "[3, 2, 1]");
+        assert_eq!(List::<i32>::new().to_string(), "[]");
+    }
+
+    #[test]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list = list.push_left(i);
+        }
+        drop(list);
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut versions = vec![List::new()];
+            for elem in tracker.elems(0..50) {
+                let next = versions.last().unwrap().push_left(elem);
+                versions.push(next);
+            }
+            // 从中间的版本分出几条支线
+            let branch = versions[20].pop_left().pop_left().push_left(tracker.elem(100));
+            assert_eq!(branch.len(), 19);
+
+            // 还有版本引用着的元素不能被析构
+            let newest = versions.pop().unwrap();
+            versions.retain(|list| list.len() % 3 == 0);
+            assert_eq!(tracker.dropped(), 0);
+            drop(newest);
+            assert_eq!(tracker.dropped(), 50 - versions.last().unwrap().len());
+            drop(versions);
+            assert_eq!(tracker.dropped(), 50 - 18);
+            drop(branch);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..10) {
+                list = list.push_left(elem);
+            }
+            let shared = list.clone();
+            list = list.push_left(tracker.panicking_elem(10));
+            for elem in tracker.elems(11..20) {
+                list = list.push_left(elem);
+            }
+            assert!(panics(|| drop(list)));
+            assert_eq!(tracker.dropped(), 10);
+            drop(shared);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    // 析构到一半 panic 时, 剩下的节点不能落到局部变量里被递归析构, 否则长链表会爆栈
+    // miri 下跑十万个节点太慢了
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn panic_in_elem_drop_long_list() {
+        use crate::testkit::{panics, Tracker};
+
+        let tracker = Tracker::new();
+        let mut list = List::new();
+        for elem in tracker.elems(0..99_990) {
+            list = list.push_left(elem);
+        }
+        list = list.push_left(tracker.panicking_elem(99_990));
+        for elem in tracker.elems(99_991..100_000) {
+            list = list.push_left(elem);
+        }
+        assert!(panics(|| drop(list)));
+        assert_eq!(tracker.dropped(), 100_000);
+        tracker.assert_all_dropped_once();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn decode_error_drops_each_elem_once() {
+        use super::SharedVersions;
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let nodes = vec![
+                (tracker.elem(0), None),
+                (tracker.elem(1), Some(0)),
+                (tracker.elem(2), Some(5)),
+                (tracker.elem(3), Some(2)),
+            ];
+            assert!(SharedVersions::decode(nodes, vec![Some(1)]).is_err());
+            tracker.assert_all_dropped_once();
+
+            let nodes = vec![(tracker.elem(0), None), (tracker.elem(1), Some(0))];
+            assert!(SharedVersions::decode(nodes, vec![Some(1), Some(2)]).is_err());
+            tracker.assert_all_dropped_once();
+        });
+    }
+}
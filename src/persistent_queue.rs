@@ -0,0 +1,653 @@
+//! 用两个 [`persistent::List`](crate::persistent::List) 拼出来的不可变(持久化)队列
+//!
+//! - [`Queue`]: 经典的双栈队列, 均摊 O(1)
+//! - [`RealTimeQueue`]: Hood-Melville 实时队列, 每次操作最坏 O(1)
+//!
+//! 和 list8 一样, 每次 push/pop 都返回一个新版本, 旧版本保持不变, 适合给工作队列做快照.
+//! 元素放在 `Rc` 里, 在两个栈之间搬运时只复制指针, 不要求 `T: Clone`.
+
+use crate::persistent::List;
+use crate::traits::PersistentQueue;
+use std::fmt;
+use std::rc::Rc;
+
+/*
+双栈队列:
+    front: 队头在栈顶, 从这里 pop
+    back:  队尾在栈顶, 往这里 push
+
+    队列 [1, 2, 3, 4, 5]:
+    [front] -> (1) -> (2) -> (3)
+    [back]  -> (5) -> (4)
+
+不变量: front 为空时 back 也为空, 这样 peek 只需要看 front
+front 被 pop 空的时候, 把 back 整个反转过来当作新的 front
+ */
+
+/// 双栈实现的不可变队列, `push_right`/`pop_left` 均摊 O(1)
+///
+/// 注意均摊分析在持久化的场景下不成立: 如果反复对同一个 "front 只剩一个元素" 的旧版本 pop,
+/// 每次都要反转整个 back. 需要最坏情况保证时用 [`RealTimeQueue`].
+pub struct Queue<T> {
+    front: List<Rc<T>>,
+    back: List<Rc<T>>,
+    len: usize,
+}
+
+// 反转一个不可变链表, 返回新链表, 原链表不变
+fn reverse<T>(list: &List<Rc<T>>) -> List<Rc<T>> {
+    list.iter()
+        .fold(List::new(), |acc, elem| acc.push_left(elem.clone()))
+}
+
+impl<T> Queue<T> {
+    /// 创建一个空队列
+    pub fn new() -> Self {
+        Queue {
+            front: List::new(),
+            back: List::new(),
+            len: 0,
+        }
+    }
+
+    // 维护 "front 为空时 back 也为空" 的不变量
+    fn check(front: List<Rc<T>>, back: List<Rc<T>>, len: usize) -> Self {
+        if front.peek_left().is_none() {
+            Queue {
+                front: reverse(&back),
+                back: List::new(),
+                len,
+            }
+        } else {
+            Queue { front, back, len }
+        }
+    }
+
+    /// 元素个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 返回在队尾添加了 `elem` 的新队列
+    pub fn push_right(&self, elem: T) -> Self {
+        Self::check(self.front.clone(), self.back.push_left(Rc::new(elem)), self.len + 1)
+    }
+
+    /// 返回去掉队头元素的新队列, 空队列返回空队列
+    pub fn pop_left(&self) -> Self {
+        if self.is_empty() {
+            return Queue::new();
+        }
+        Self::check(self.front.pop_left(), self.back.clone(), self.len - 1)
+    }
+
+    /// 返回队头元素
+    pub fn peek_left(&self) -> Option<&T> {
+        self.front.peek_left().map(|elem| &**elem)
+    }
+
+    /// 从队头到队尾返回元素的引用
+    pub fn iter(&self) -> Iter<'_, T> {
+        // back 是倒着存的, 需要先收集起来再反过来
+        let mut elems: Vec<&T> = self.front.iter().map(|elem| &**elem).collect();
+        let start = elems.len();
+        elems.extend(self.back.iter().map(|elem| &**elem));
+        elems[start..].reverse();
+        Iter(elems.into_iter())
+    }
+}
+
+// 复制一个版本只需要复制两个头指针
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 按出队的顺序输出, 形如 [1, 2, 3]
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T> PersistentQueue<T> for Queue<T> {
+    fn push_right(&self, elem: T) -> Self {
+        Queue::push_right(self, elem)
+    }
+
+    fn pop_left(&self) -> Self {
+        Queue::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        Queue::peek_left(self)
+    }
+
+    fn len(&self) -> usize {
+        Queue::len(self)
+    }
+}
+
+/// 按不可变引用迭代, 由 [`Queue::iter`]/[`RealTimeQueue::iter`] 创建
+pub struct Iter<'a, T>(std::vec::IntoIter<&'a T>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/*
+Hood-Melville 实时队列 (Okasaki, Purely Functional Data Structures, 8.2.1)
+
+双栈队列慢在反转 back 的那一次操作, 实时队列把反转拆成很多小步,
+每次 push/pop 顺便做两步, 保证在 front 被用完之前反转已经完成:
+
+    front.len >= back.len 时什么都不用做
+    back.len == front.len + 1 时开始一次 "旋转", 目标是得到新的 front = front ++ reverse(back)
+
+旋转分为两个阶段:
+    Reversing: 同时把 f 反转到 f_rev, 把 r 反转到 r_rev
+    Appending: 再把 f_rev 一个个压回 r_rev, 得到 f ++ reverse(r)
+
+旋转期间还在用旧的 front 做 pop, 被 pop 掉的元素在 f_rev 里的拷贝已经失效了,
+ok 记录 f_rev 里还有效的元素个数, Appending 时只需要搬运有效的那些
+ */
+enum Rotation<T> {
+    Idle,
+    Reversing {
+        ok: usize,
+        f: List<Rc<T>>,
+        f_rev: List<Rc<T>>,
+        r: List<Rc<T>>,
+        r_rev: List<Rc<T>>,
+    },
+    Appending {
+        ok: usize,
+        f_rev: List<Rc<T>>,
+        r_rev: List<Rc<T>>,
+    },
+    Done(List<Rc<T>>),
+}
+
+impl<T> Rotation<T> {
+    // 旋转向前推进一步
+    fn exec(&self) -> Self {
+        match self {
+            Rotation::Reversing { ok, f, f_rev, r, r_rev } => {
+                let y = r.peek_left().expect("rotation starts with |r| = |f| + 1").clone();
+                match f.peek_left() {
+                    Some(x) => Rotation::Reversing {
+                        ok: ok + 1,
+                        f: f.pop_left(),
+                        f_rev: f_rev.push_left(x.clone()),
+                        r: r.pop_left(),
+                        r_rev: r_rev.push_left(y),
+                    },
+                    // f 用完时 r 里恰好还剩一个元素
+                    None => Rotation::Appending {
+                        ok: *ok,
+                        f_rev: f_rev.clone(),
+                        r_rev: r_rev.push_left(y),
+                    },
+                }
+            }
+            Rotation::Appending { ok: 0, r_rev, .. } => Rotation::Done(r_rev.clone()),
+            Rotation::Appending { ok, f_rev, r_rev } => {
+                let x = f_rev.peek_left().expect("ok never exceeds |f_rev|").clone();
+                Rotation::Appending {
+                    ok: ok - 1,
+                    f_rev: f_rev.pop_left(),
+                    r_rev: r_rev.push_left(x),
+                }
+            }
+            other => other.clone(),
+        }
+    }
+
+    // 旧的 front 被 pop 了一个元素, 它在 f_rev 里的拷贝失效了
+    fn invalidate(&self) -> Self {
+        match self {
+            Rotation::Reversing { ok, f, f_rev, r, r_rev } => Rotation::Reversing {
+                ok: ok - 1,
+                f: f.clone(),
+                f_rev: f_rev.clone(),
+                r: r.clone(),
+                r_rev: r_rev.clone(),
+            },
+            // 有效的元素都已经搬完了, r_rev 头上那个正是刚被 pop 掉的元素
+            Rotation::Appending { ok: 0, r_rev, .. } => Rotation::Done(r_rev.pop_left()),
+            Rotation::Appending { ok, f_rev, r_rev } => Rotation::Appending {
+                ok: ok - 1,
+                f_rev: f_rev.clone(),
+                r_rev: r_rev.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Rotation<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Rotation::Idle => Rotation::Idle,
+            Rotation::Reversing { ok, f, f_rev, r, r_rev } => Rotation::Reversing {
+                ok: *ok,
+                f: f.clone(),
+                f_rev: f_rev.clone(),
+                r: r.clone(),
+                r_rev: r_rev.clone(),
+            },
+            Rotation::Appending { ok, f_rev, r_rev } => Rotation::Appending {
+                ok: *ok,
+                f_rev: f_rev.clone(),
+                r_rev: r_rev.clone(),
+            },
+            Rotation::Done(list) => Rotation::Done(list.clone()),
+        }
+    }
+}
+
+/// Hood-Melville 实时队列, `push_right`/`pop_left`/`peek_left` 最坏 O(1), 旧版本随便用
+pub struct RealTimeQueue<T> {
+    // 逻辑上 front 部分的元素个数, 包括还在旋转中的元素
+    len_front: usize,
+    front: List<Rc<T>>,
+    state: Rotation<T>,
+    len_back: usize,
+    back: List<Rc<T>>,
+}
+
+impl<T> RealTimeQueue<T> {
+    /// 创建一个空队列
+    pub fn new() -> Self {
+        RealTimeQueue {
+            len_front: 0,
+            front: List::new(),
+            state: Rotation::Idle,
+            len_back: 0,
+            back: List::new(),
+        }
+    }
+
+    // 旋转推进两步, 完成时用结果替换 front
+    fn exec2(len_front: usize, front: List<Rc<T>>, state: Rotation<T>, len_back: usize, back: List<Rc<T>>) -> Self {
+        match state.exec().exec() {
+            Rotation::Done(new_front) => RealTimeQueue {
+                len_front,
+                front: new_front,
+                state: Rotation::Idle,
+                len_back,
+                back,
+            },
+            state => RealTimeQueue {
+                len_front,
+                front,
+                state,
+                len_back,
+                back,
+            },
+        }
+    }
+
+    // back 比 front 长的时候开始一次新的旋转
+    fn check(len_front: usize, front: List<Rc<T>>, state: Rotation<T>, len_back: usize, back: List<Rc<T>>) -> Self {
+        if len_back <= len_front {
+            Self::exec2(len_front, front, state, len_back, back)
+        } else {
+            let state = Rotation::Reversing {
+                ok: 0,
+                f: front.clone(),
+                f_rev: List::new(),
+                r: back,
+                r_rev: List::new(),
+            };
+            Self::exec2(len_front + len_back, front, state, 0, List::new())
+        }
+    }
+
+    /// 元素个数
+    pub fn len(&self) -> usize {
+        self.len_front + self.len_back
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len_front == 0
+    }
+
+    /// 返回在队尾添加了 `elem` 的新队列
+    pub fn push_right(&self, elem: T) -> Self {
+        Self::check(
+            self.len_front,
+            self.front.clone(),
+            self.state.clone(),
+            self.len_back + 1,
+            self.back.push_left(Rc::new(elem)),
+        )
+    }
+
+    /// 返回去掉队头元素的新队列, 空队列返回空队列
+    pub fn pop_left(&self) -> Self {
+        if self.is_empty() {
+            return RealTimeQueue::new();
+        }
+        Self::check(
+            self.len_front - 1,
+            self.front.pop_left(),
+            self.state.invalidate(),
+            self.len_back,
+            self.back.clone(),
+        )
+    }
+
+    /// 返回队头元素
+    pub fn peek_left(&self) -> Option<&T> {
+        self.front.peek_left().map(|elem| &**elem)
+    }
+
+    /// 从队头到队尾返回元素的引用
+    ///
+    /// 旋转进行到一半时元素分散在好几个链表里, 这里会先把它们按顺序收集起来, O(n)
+    pub fn iter(&self) -> Iter<'_, T> {
+        fn extend_rev<'a, T>(elems: &mut Vec<&'a T>, list: &'a List<Rc<T>>) {
+            let start = elems.len();
+            elems.extend(list.iter().map(|elem| &**elem));
+            elems[start..].reverse();
+        }
+
+        let mut elems: Vec<&T> = Vec::with_capacity(self.len());
+        // 旧的 front 里都是有效元素, 排在最前面
+        elems.extend(self.front.iter().map(|elem| &**elem));
+        let rotating = self.len_front - elems.len();
+        // 然后是旋转中的那部分, 也就是开始旋转时 back 反转后的结果
+        match &self.state {
+            Rotation::Reversing { r, r_rev, .. } => {
+                extend_rev(&mut elems, r);
+                elems.extend(r_rev.iter().map(|elem| &**elem));
+            }
+            Rotation::Appending { r_rev, .. } => {
+                // r_rev 前面是从 f_rev 搬过来的拷贝, 和 front 里的重复了, 只取最后 rotating 个
+                let skip = r_rev.iter().count() - rotating;
+                elems.extend(r_rev.iter().skip(skip).map(|elem| &**elem));
+            }
+            Rotation::Idle | Rotation::Done(_) => {}
+        }
+        extend_rev(&mut elems, &self.back);
+        Iter(elems.into_iter())
+    }
+}
+
+impl<T> Clone for RealTimeQueue<T> {
+    fn clone(&self) -> Self {
+        RealTimeQueue {
+            len_front: self.len_front,
+            front: self.front.clone(),
+            state: self.state.clone(),
+            len_back: self.len_back,
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T> Default for RealTimeQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 同样按出队的顺序输出, 不暴露内部的轮转状态
+impl<T: fmt::Debug> fmt::Debug for RealTimeQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for RealTimeQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T> PersistentQueue<T> for RealTimeQueue<T> {
+    fn push_right(&self, elem: T) -> Self {
+        RealTimeQueue::push_right(self, elem)
+    }
+
+    fn pop_left(&self) -> Self {
+        RealTimeQueue::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        RealTimeQueue::peek_left(self)
+    }
+
+    fn len(&self) -> usize {
+        RealTimeQueue::len(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Queue, RealTimeQueue};
+    use crate::traits::PersistentQueue;
+    use std::collections::VecDeque;
+
+    fn basics<Q: PersistentQueue<i32> + Default>() {
+        let q = Q::default();
+        assert_eq!(q.peek_left(), None);
+
+        let q = q.push_right(1).push_right(2).push_right(3);
+        assert_eq!(q.peek_left(), Some(&1));
+
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), Some(&2));
+
+        let q = q.push_right(4).push_right(5);
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), Some(&3));
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), Some(&4));
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), Some(&5));
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), None);
+
+        // Make sure empty queue works
+        let q = q.pop_left();
+        assert_eq!(q.peek_left(), None);
+    }
+
+    #[test]
+    fn queue_basics() {
+        basics::<Queue<i32>>();
+    }
+
+    #[test]
+    fn real_time_basics() {
+        basics::<RealTimeQueue<i32>>();
+    }
+
+    #[test]
+    fn fmt() {
+        let q = Queue::new().push_right(1).push_right(2).push_right(3).pop_left();
+        assert_eq!(format!("{:?}", q), "[2, 3]");
+        assert_eq!(q.to_string(), "[2, 3]");
+
+        let q = RealTimeQueue::new().push_right(1).push_right(2).push_right(3).pop_left();
+        assert_eq!(format!("{:?}", q), "[2, 3]");
+        assert_eq!(format!("{:#?}", q), "[\n    2,\n    3,\n]");
+        assert_eq!(q.to_string(), "[2, 3]");
+    }
+
+    #[test]
+    fn old_versions_stay_valid() {
+        let v1 = Queue::new().push_right(1).push_right(2);
+        let v2 = v1.pop_left().push_right(3);
+        let v3 = v1.push_right(4);
+        assert_eq!(v1.iter().collect::<Vec<_>>(), vec![&1, &2]);
+        assert_eq!(v2.iter().collect::<Vec<_>>(), vec![&2, &3]);
+        assert_eq!(v3.iter().collect::<Vec<_>>(), vec![&1, &2, &4]);
+
+        let v1 = RealTimeQueue::new().push_right(1).push_right(2);
+        let v2 = v1.pop_left().push_right(3);
+        let v3 = v1.push_right(4);
+        assert_eq!(v1.iter().collect::<Vec<_>>(), vec![&1, &2]);
+        assert_eq!(v2.iter().collect::<Vec<_>>(), vec![&2, &3]);
+        assert_eq!(v3.iter().collect::<Vec<_>>(), vec![&1, &2, &4]);
+    }
+
+    // 随机地从任意一个历史版本出发做操作, 和 VecDeque 的快照对比
+    #[test]
+    fn random_versions_match_vec_deque() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut rand = move |n: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as usize
+        };
+
+        let mut queues = vec![Queue::new()];
+        let mut real_time = vec![RealTimeQueue::new()];
+        let mut models = vec![VecDeque::new()];
+        for i in 0..3000 {
+            let v = rand(models.len());
+            let (q, rt, mut model) = (&queues[v], &real_time[v], models[v].clone());
+            let (q, rt) = if rand(3) == 0 {
+                model.pop_front();
+                (q.pop_left(), rt.pop_left())
+            } else {
+                model.push_back(i);
+                (q.push_right(i), rt.push_right(i))
+            };
+
+            assert_eq!(q.peek_left(), model.front());
+            assert_eq!(rt.peek_left(), model.front());
+            assert_eq!(q.len(), model.len());
+            assert_eq!(rt.len(), model.len());
+            assert!(q.iter().eq(model.iter()));
+            assert!(rt.iter().eq(model.iter()));
+            assert_eq!(rt.iter().len(), model.len());
+
+            queues.push(q);
+            real_time.push(rt);
+            models.push(model);
+        }
+
+        // 所有历史版本依然完好
+        for ((q, rt), model) in queues.iter().zip(&real_time).zip(&models) {
+            assert!(q.iter().eq(model.iter()));
+            assert!(rt.iter().eq(model.iter()));
+        }
+    }
+
+    #[test]
+    fn drain_real_time() {
+        let mut q = RealTimeQueue::new();
+        for i in 0..1000 {
+            q = q.push_right(i);
+            if i % 3 == 0 {
+                q = q.pop_left();
+            }
+        }
+        let mut expected = 334..1000;
+        while let Some(&x) = q.peek_left() {
+            assert_eq!(Some(x), expected.next());
+            q = q.pop_left();
+        }
+        assert_eq!(expected.next(), None);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut q = Queue::new();
+            let mut rt = RealTimeQueue::new();
+            let mut versions = Vec::new();
+            for (i, elem) in tracker.elems(0..100).into_iter().enumerate() {
+                rt = rt.push_right(elem.clone());
+                q = q.push_right(elem);
+                if i % 7 == 0 {
+                    q = q.pop_left();
+                    rt = rt.pop_left();
+                    versions.push((q.clone(), rt.clone()));
+                }
+            }
+            assert_eq!(q.iter().count(), rt.iter().count());
+            drop((q, rt));
+            // 旧版本依然持有自己的元素
+            assert!(tracker.dropped() < tracker.created());
+            drop(versions);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut q = Queue::new();
+            let mut rt = RealTimeQueue::new();
+            let elem = |i| if i == 10 { tracker.panicking_elem(i) } else { tracker.elem(i) };
+            for i in 0..20 {
+                q = q.push_right(elem(i));
+                rt = rt.push_right(elem(i));
+            }
+            q = q.pop_left();
+            rt = rt.pop_left();
+            assert!(panics(|| drop(q)));
+            assert!(panics(|| drop(rt)));
+            tracker.assert_all_dropped_once();
+        });
+    }
+}
@@ -0,0 +1,118 @@
+//! 基于 `Arc` 的不可变(持久化)链表栈, 可以在线程之间共享
+//!
+//! 和 [`persistent::List`](crate::persistent::List) 是同一个泛型链表, 只是节点由 `Arc` 管理。
+//! 节点创建之后就不会再改变, 所以只要 `T: Send + Sync`,
+//! 不同线程就可以各自持有共享同一段尾部的不同版本.
+
+use crate::shared::{self, SharedPointer};
+#[cfg(feature = "serde")]
+use crate::versions::{self, Nodes};
+use std::sync::Arc;
+
+/// 用 `Arc` 管理节点, 可以跨线程共享
+pub enum ArcPointer {}
+
+impl SharedPointer for ArcPointer {
+    type Ptr<N> = Arc<N>;
+
+    fn new<N>(node: N) -> Arc<N> {
+        Arc::new(node)
+    }
+
+    fn strong_count<N>(ptr: &Arc<N>) -> usize {
+        Arc::strong_count(ptr)
+    }
+
+    fn into_inner<N>(ptr: Arc<N>) -> Option<N> {
+        // 这里不能用 Arc::try_unwrap:
+        // 两个线程同时 drop 共享同一个节点的链表时, 可能都看到引用计数是 2 而失败,
+        // 最后一个引用在 try_unwrap 返回的 Err 里被释放, 又变回了递归 drop
+        // Arc::into_inner 保证在所有持有者里恰好有一个能拿到节点
+        Arc::into_inner(ptr)
+    }
+}
+
+/// 线程安全的不可变链表, 每次 push/pop 都会返回一个新的版本, 旧版本依然可用
+pub type List<T> = shared::List<T, ArcPointer>;
+
+/// 按不可变引用迭代, 由 [`List::iter`](shared::List::iter) 创建
+pub type Iter<'a, T> = shared::Iter<'a, T, ArcPointer>;
+
+/// 一批可能共享尾部的链表版本, 序列化时每个节点只写一次, 需要打开 `serde` feature
+///
+/// 格式和 [`persistent::SharedVersions`](crate::persistent::SharedVersions) 完全一样,
+/// 两者写出的数据可以互相读取
+#[cfg(feature = "serde")]
+pub struct SharedVersions<T>(pub Vec<List<T>>);
+
+#[cfg(feature = "serde")]
+impl<T> SharedVersions<T> {
+    pub(crate) fn encode(&self) -> (Nodes<&T>, Vec<Option<usize>>) {
+        versions::encode(&self.0)
+    }
+
+    pub(crate) fn decode(nodes: Nodes<T>, heads: Vec<Option<usize>>) -> Result<Self, String> {
+        versions::decode(nodes, heads).map(SharedVersions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn send_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<List<i32>>();
+        is_send_sync::<super::Iter<i32>>();
+    }
+
+    #[test]
+    fn share_tail_across_threads() {
+        let mut base = List::new();
+        for i in 0..1000 {
+            base = base.push_left(i);
+        }
+        let base = Arc::new(base);
+
+        // 每个线程在共享的尾部上面加自己的元素
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let base = base.clone();
+                thread::spawn(move || {
+                    let mine = base.push_left(t * 10000).push_left(t * 10000 + 1);
+                    assert_eq!(mine.iter().count(), 1002);
+                    assert_eq!(mine.pop_left().pop_left().iter().sum::<i32>(), (0..1000).sum::<i32>());
+                    mine
+                })
+            })
+            .collect();
+
+        let versions: Vec<List<i32>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        drop(base);
+        for (t, v) in versions.iter().enumerate() {
+            assert_eq!(v.peek_left(), Some(&(t as i32 * 10000 + 1)));
+            assert_eq!(v.iter().nth(2), Some(&999));
+        }
+    }
+
+    #[test]
+    fn concurrent_drop_of_shared_tail() {
+        let mut base = List::new();
+        for i in 0..100000 {
+            base = base.push_left(i);
+        }
+        // 多个线程同时释放共享同一条长尾部的链表, 不能有递归 drop 爆栈
+        let versions: Vec<_> = (0..8).map(|t| base.push_left(t)).collect();
+        drop(base);
+        let handles: Vec<_> = versions
+            .into_iter()
+            .map(|v| thread::spawn(move || drop(v)))
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
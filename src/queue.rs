@@ -0,0 +1,570 @@
+//! 带尾指针的单向链表队列
+//!
+//! [`stack::List`](crate::stack::List) 只能在头部操作, 想在尾部 push 需要先走到最后一个节点;
+//! 这里额外记录一个指向尾节点的裸指针, 让 `push_right` 和 `pop_left` 都是 O(1):
+//! ```text
+//! [head, tail] -> (1, ptr) -> (2, ptr) -> (3, null)
+//!       |                                  ^
+//!       +----------------------------------+
+//! ```
+
+use crate::dot::{Dot, Edge};
+use crate::teardown;
+use crate::traits::{Queue, Stack};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr;
+
+/*
+为什么 head 不用 Option<Box<Node<T>>>:
+Box 对它指向的内存有独占的语义, 每次 Box 被移动或者通过它取 &mut, 之前从它派生出来的裸指针
+(也就是 tail) 在 Stacked Borrows 模型下都会失效, 之后再通过 tail 写内存就是未定义行为,
+miri 会直接报错
+
+所以这里所有的链接都用裸指针, 节点通过 Box::into_raw 分配、Box::from_raw 释放,
+只在最外层对外暴露安全的接口。链表自己只保存裸指针, peek/iter/iter_mut 把裸指针转成引用交给调用方,
+这些引用借用着整个链表, 活着的时候链表不会再通过裸指针改动节点, 引用也不会再被转回裸指针存起来
+ */
+type Link<T> = *mut Node<T>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+/// 单向链表队列, 尾部 push、头部 pop 都是 O(1)
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // 告诉编译器我们拥有 T
+    _marker: PhantomData<T>,
+}
+
+impl<T> List<T> {
+    /// 创建一个空队列
+    pub fn new() -> Self {
+        List {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 元素个数, O(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 在队列尾部添加元素
+    pub fn push_right(&mut self, elem: T) {
+        unsafe {
+            let new_tail = Box::into_raw(Box::new(Node {
+                elem,
+                next: ptr::null_mut(),
+            }));
+
+            if self.tail.is_null() {
+                // 空队列, 头尾都指向新节点
+                self.head = new_tail;
+            } else {
+                (*self.tail).next = new_tail;
+            }
+            self.tail = new_tail;
+        }
+        self.len += 1;
+    }
+
+    /// 在队列头部添加元素
+    pub fn push_left(&mut self, elem: T) {
+        let new_head = Box::into_raw(Box::new(Node {
+            elem,
+            next: self.head,
+        }));
+        if self.tail.is_null() {
+            self.tail = new_head;
+        }
+        self.head = new_head;
+        self.len += 1;
+    }
+
+    /// 从队列头部移出元素
+    pub fn pop_left(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+        unsafe {
+            // 拿回节点的所有权, 函数结束时节点的内存被释放
+            let head = Box::from_raw(self.head);
+            self.head = head.next;
+            if self.head.is_null() {
+                // 队列空了, tail 不能再指向已经被释放的节点
+                self.tail = ptr::null_mut();
+            }
+            self.len -= 1;
+            Some(head.elem)
+        }
+    }
+
+    /// 返回队列头部元素
+    pub fn peek_left(&self) -> Option<&T> {
+        unsafe { self.head.as_ref().map(|node| &node.elem) }
+    }
+
+    /// 返回队列头部元素, 可修改
+    pub fn peek_left_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.as_mut().map(|node| &mut node.elem) }
+    }
+
+    /// 返回队列尾部元素
+    pub fn peek_right(&self) -> Option<&T> {
+        unsafe { self.tail.as_ref().map(|node| &node.elem) }
+    }
+
+    /// 返回队列尾部元素, 可修改
+    pub fn peek_right_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.as_mut().map(|node| &mut node.elem) }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 从头到尾输出, 形如 [1, 2, 3]
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: fmt::Debug> List<T> {
+    /// 导出 Graphviz DOT 格式的内存布局图, 可以用 `dot -Tsvg` 渲染
+    ///
+    /// 画出 next 边, 以及链表的 head 和 tail 两个指针
+    pub fn to_dot(lists: &[(&str, &Self)]) -> String {
+        let mut dot = Dot::new();
+        for (label, list) in lists {
+            let handle = dot.head(label);
+            let mut from = handle.clone();
+            let mut edge = Edge::Head;
+            let mut cur = list.head;
+            while !cur.is_null() {
+                // 安全: 只读, &self 保证期间没有人修改链表
+                let node = unsafe { &*cur };
+                let (name, fresh) = dot.node(cur, &node.elem, None);
+                dot.edge(&from, &name, edge);
+                if !fresh {
+                    break;
+                }
+                from = name;
+                edge = Edge::Next;
+                cur = node.next;
+            }
+            if let Some(tail) = dot.name(list.tail) {
+                dot.edge(&handle, &tail, Edge::Tail);
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // 剩下的节点只有裸指针指着, 元素析构时 panic 也要接着释放
+        teardown::drop_all(self, List::pop_left);
+    }
+}
+
+// 裸指针默认既不是 Send 也不是 Sync, 但这里的节点只被这一个队列拥有,
+// 和 Box<Node<T>> 一样, 只要 T 满足条件就可以跨线程
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+impl<T> Stack<T> for List<T> {
+    type Ref<'a> = &'a T where T: 'a;
+    type RefMut<'a> = &'a mut T where T: 'a;
+
+    fn push_left(&mut self, elem: T) {
+        List::push_left(self, elem)
+    }
+
+    fn pop_left(&mut self) -> Option<T> {
+        List::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        List::peek_left(self)
+    }
+
+    fn peek_left_mut(&mut self) -> Option<&mut T> {
+        List::peek_left_mut(self)
+    }
+
+    fn len(&self) -> usize {
+        List::len(self)
+    }
+}
+
+impl<T> Queue<T> for List<T> {
+    fn push_right(&mut self, elem: T) {
+        List::push_right(self, elem)
+    }
+}
+
+/// 按值迭代, 由 [`List::into_iter`](IntoIterator::into_iter) 创建
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_left()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+/// 按不可变引用迭代, 由 [`List::iter`] 创建
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的引用
+    pub fn iter(&self) -> Iter<'_, T> {
+        unsafe {
+            Iter {
+                next: self.head.as_ref(),
+                len: self.len,
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.next.map(|node| {
+                self.next = node.next.as_ref();
+                self.len -= 1;
+                &node.elem
+            })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// 按可变引用迭代, 由 [`List::iter_mut`] 创建
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的可变引用
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        unsafe {
+            IterMut {
+                next: self.head.as_mut(),
+                len: self.len,
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.next.take().map(|node| {
+                self.next = node.next.as_mut();
+                self.len -= 1;
+                &mut node.elem
+            })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_left(), None);
+
+        // Populate list
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(1));
+        assert_eq!(list.pop_left(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_right(4);
+        list.push_right(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(3));
+        assert_eq!(list.pop_left(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_left(), Some(5));
+        assert_eq!(list.pop_left(), None);
+
+        // Check the exhaustion case fixed the pointer right
+        list.push_right(6);
+        list.push_right(7);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(6));
+        assert_eq!(list.pop_left(), Some(7));
+        assert_eq!(list.pop_left(), None);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+        list.push_right(1);
+        list.push_left(0);
+        list.push_right(2);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().len(), 3);
+        assert_eq!(list.iter_mut().skip(2).len(), 1);
+        list.pop_left();
+        assert_eq!(list.len(), 2);
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(iter.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn push_both_ends() {
+        let mut list = List::new();
+        list.push_left(2);
+        list.push_right(3);
+        list.push_left(1);
+        assert_eq!(list.peek_right(), Some(&3));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+        // 只剩一个元素时, 头尾是同一个节点
+        list.pop_left();
+        list.pop_left();
+        assert_eq!(list.peek_left(), list.peek_right());
+        list.push_left(0);
+        list.push_right(4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert!(list.peek_left().is_none());
+        assert!(list.peek_right().is_none());
+        assert!(list.peek_left_mut().is_none());
+        assert!(list.peek_right_mut().is_none());
+
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        assert_eq!(list.peek_left(), Some(&1));
+        assert_eq!(list.peek_right(), Some(&3));
+        if let Some(tail) = list.peek_right_mut() {
+            *tail *= 10;
+        }
+        if let Some(head) = list.peek_left_mut() {
+            *head *= 10;
+        }
+        assert_eq!(list.pop_left(), Some(10));
+        // 修改尾部之后继续 push, 确认 tail 指针没有失效
+        list.push_right(4);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &30, &4]);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+
+        for elem in list.iter_mut() {
+            *elem += 1;
+        }
+        // iter_mut 之后 tail 依然可以用
+        list.push_right(5);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        assert!(list.pop_left() == Some(1));
+        list.push_right(4);
+        assert!(list.pop_left() == Some(2));
+        list.push_right(5);
+
+        assert!(list.peek_left() == Some(&3));
+        list.push_right(6);
+        if let Some(x) = list.peek_left_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek_left() == Some(&30));
+        assert!(list.pop_left() == Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&400));
+        assert_eq!(iter.next(), Some(&500));
+        assert_eq!(iter.next(), Some(&600));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        assert!(list.pop_left() == Some(400));
+        if let Some(x) = list.peek_left_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek_left() == Some(&5000));
+        list.push_right(7);
+
+        // Drop it on the ground and let the dtor exercise itself
+    }
+
+    #[test]
+    fn fmt() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        list.push_right(2);
+        list.push_right(3);
+        list.push_left(1);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(format!("{:#?}", list), "[\n    1,\n    2,\n    3,\n]");
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+    }
+
+    // miri 下跑十万个节点太慢了
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list.push_right(i.to_string());
+        }
+        drop(list);
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..50) {
+                list.push_right(elem);
+            }
+            list.push_left(tracker.elem(-1));
+            for _ in 0..20 {
+                drop(list.pop_left());
+            }
+            list.peek_right_mut().unwrap().value += 1;
+            for elem in list.iter_mut() {
+                elem.value *= 2;
+            }
+            assert_eq!(list.iter().count(), 31);
+
+            let mut iter = list.into_iter();
+            drop(iter.next());
+            drop(iter);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..10) {
+                list.push_right(elem);
+            }
+            list.push_right(tracker.panicking_elem(10));
+            for elem in tracker.elems(11..20) {
+                list.push_right(elem);
+            }
+            assert!(panics(|| drop(list)));
+            tracker.assert_all_dropped_once();
+        });
+    }
+}
@@ -0,0 +1,22 @@
+//! `drain` 等按下标范围操作的方法共用的范围换算
+
+use std::ops::{Bound, RangeBounds};
+
+/// 把 range 换算成 `[start, end)`
+///
+/// `start > end` 或者 `end > len` 时 panic
+pub(crate) fn to_indices<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&n) => n,
+        Bound::Excluded(&n) => n.checked_add(1).expect("range start overflows usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&n) => n.checked_add(1).expect("range end overflows usize"),
+        Bound::Excluded(&n) => n,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "drain start {start} is greater than end {end}");
+    assert!(end <= len, "drain end {end} is out of range for list of length {len}");
+    (start, end)
+}
@@ -0,0 +1,312 @@
+//! `serde` feature 打开时, 为链表实现 `Serialize`/`Deserialize`
+//!
+//! 都按迭代的顺序序列化成普通的序列, 和 `Vec` 的格式一样, 所以可以和 `Vec` 互相转换。
+//! 反序列化时逐个读取元素再挂到链表上, 不会递归, 很长的输入也不会爆栈
+//!
+//! 需要保留多个不可变链表之间的共享时, 用 [`persistent::SharedVersions`] 或
+//! [`persistent_sync::SharedVersions`] 包一层, 两者的格式一样
+
+use crate::shared::{self, SharedPointer};
+use crate::{deque, persistent, persistent_sync, stack};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+impl<T: Serialize> Serialize for stack::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// persistent 和 persistent_sync 的链表是同一个泛型类型
+impl<T: Serialize, P: SharedPointer> Serialize for shared::List<T, P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// deque 的迭代器返回的是元素句柄, 要先 borrow 才能拿到元素
+impl<T: Serialize> Serialize for deque::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for elem in self.iter() {
+            seq.serialize_element(&*elem.borrow())?;
+        }
+        seq.end()
+    }
+}
+
+// 各种链表共用的 Visitor, 把读到的元素依次交给 build 组装成链表
+struct SeqVisitor<T, L, B> {
+    build: B,
+    _marker: PhantomData<fn() -> (T, L)>,
+}
+
+impl<'de, T, L, B> Visitor<'de> for SeqVisitor<T, L, B>
+where
+    T: Deserialize<'de>,
+    B: FnOnce(&mut dyn Iterator<Item = T>) -> L,
+{
+    type Value = L;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<L, A::Error> {
+        // 出错时先停止迭代, 把错误记下来, 组装完再返回
+        let mut error = None;
+        let mut elems = std::iter::from_fn(|| match seq.next_element() {
+            Ok(elem) => elem,
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        });
+        let list = (self.build)(&mut elems);
+        match error {
+            Some(e) => Err(e),
+            None => Ok(list),
+        }
+    }
+}
+
+fn deserialize_seq<'de, D, T, L, B>(deserializer: D, build: B) -> Result<L, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+    B: FnOnce(&mut dyn Iterator<Item = T>) -> L,
+{
+    deserializer.deserialize_seq(SeqVisitor {
+        build,
+        _marker: PhantomData,
+    })
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for stack::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // FromIterator 会保持顺序, 第一个元素在头部
+        deserialize_seq(deserializer, |elems| elems.collect())
+    }
+}
+
+// 不可变链表只能从头部 push, 所以先把元素收集起来, 再倒着 push
+impl<'de, T: Deserialize<'de>, P: SharedPointer> Deserialize<'de> for shared::List<T, P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, |elems| {
+            let elems: Vec<T> = elems.collect();
+            elems
+                .into_iter()
+                .rev()
+                .fold(shared::List::new(), |list, elem| list.push_left(elem))
+        })
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for deque::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, |elems| {
+            let mut list = deque::List::new();
+            for elem in elems {
+                list.push_right(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for persistent::SharedVersions<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.encode().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for persistent::SharedVersions<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (nodes, heads) = Deserialize::deserialize(deserializer)?;
+        persistent::SharedVersions::decode(nodes, heads).map_err(de::Error::custom)
+    }
+}
+
+impl<T: Serialize> Serialize for persistent_sync::SharedVersions<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.encode().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for persistent_sync::SharedVersions<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (nodes, heads) = Deserialize::deserialize(deserializer)?;
+        persistent_sync::SharedVersions::decode(nodes, heads).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::persistent::SharedVersions;
+    use crate::versions::Nodes;
+    use crate::{deque, persistent, persistent_sync, stack};
+
+    #[test]
+    fn stack_round_trip() {
+        let list: stack::List<i32> = (1..=3).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: stack::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, list);
+        assert_eq!(back.len(), 3);
+    }
+
+    #[test]
+    fn persistent_round_trip() {
+        let list = persistent::List::new().push_left(3).push_left(2).push_left(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: persistent::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(back.len(), 3);
+
+        let list = persistent_sync::List::new().push_left(2).push_left(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2]");
+        let back: persistent_sync::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.iter().collect::<Vec<_>>(), vec![&1, &2]);
+    }
+
+    #[test]
+    fn deque_round_trip() {
+        let mut list = deque::List::new();
+        list.push_right(2);
+        list.push_right(3);
+        list.push_left(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let mut back: deque::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 3);
+        assert_eq!(back.pop_right(), Some(3));
+        assert_eq!(back.pop_left(), Some(1));
+    }
+
+    #[test]
+    fn same_format_as_vec() {
+        let list: stack::List<String> = serde_json::from_str(r#"["a","b"]"#).unwrap();
+        let v: Vec<String> = serde_json::from_str(&serde_json::to_string(&list).unwrap()).unwrap();
+        assert_eq!(v, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(serde_json::from_str::<stack::List<i32>>("[1,\"x\",3]").is_err());
+        assert!(serde_json::from_str::<deque::List<i32>>("{}").is_err());
+        assert!(serde_json::from_str::<persistent::List<i32>>("[1,2").is_err());
+    }
+
+    #[test]
+    fn shared_versions_round_trip() {
+        let base = persistent::List::new().push_left(1).push_left(2);
+        let a = base.push_left(3);
+        let b = base.push_left(4);
+        let c = a.push_left(5);
+        let versions = SharedVersions(vec![a, b, c, persistent::List::new(), base]);
+
+        let json = serde_json::to_string(&versions).unwrap();
+        // 1 和 2 只写了一次, 下标从尾部开始分配
+        assert_eq!(json, "[[[1,null],[2,0],[3,1],[4,1],[5,2]],[2,3,4,null,1]]");
+
+        let SharedVersions(back) = serde_json::from_str(&json).unwrap();
+        let [a, b, c, empty, base] = <[persistent::List<i32>; 5]>::try_from(back).ok().unwrap();
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(b.iter().collect::<Vec<_>>(), vec![&4, &2, &1]);
+        assert_eq!(c.iter().collect::<Vec<_>>(), vec![&5, &3, &2, &1]);
+        assert!(empty.is_empty());
+        assert_eq!(c.len(), 4);
+
+        // 共享关系和写出去之前一样
+        assert!(a.pop_left().ptr_eq(&base));
+        assert!(b.pop_left().ptr_eq(&base));
+        assert!(c.pop_left().ptr_eq(&a));
+        assert!(!a.ptr_eq(&b));
+    }
+
+    #[test]
+    fn shared_versions_same_format_for_arc() {
+        let rc_base = persistent::List::new().push_left(1).push_left(2);
+        let rc_versions = vec![rc_base.push_left(3), rc_base.push_left(4), rc_base];
+        let arc_base = persistent_sync::List::new().push_left(1).push_left(2);
+        let arc_versions = vec![arc_base.push_left(3), arc_base.push_left(4), arc_base];
+
+        let json = serde_json::to_string(&SharedVersions(rc_versions)).unwrap();
+        let arc_json = serde_json::to_string(&persistent_sync::SharedVersions(arc_versions)).unwrap();
+        assert_eq!(json, arc_json);
+
+        // Rc 版本写出的数据可以读成 Arc 版本, 共享关系不变
+        let persistent_sync::SharedVersions(back) = serde_json::from_str(&json).unwrap();
+        let [a, b, base] = <[persistent_sync::List<i32>; 3]>::try_from(back).ok().unwrap();
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(b.len(), 3);
+        assert!(a.pop_left().ptr_eq(&base));
+        assert!(b.pop_left().ptr_eq(&base));
+        assert!(!a.ptr_eq(&b));
+
+        let bad = "[[[1,1],[2,0]],[1]]";
+        assert!(serde_json::from_str::<persistent_sync::SharedVersions<i32>>(bad).is_err());
+    }
+
+    #[test]
+    fn shared_versions_invalid_ids() {
+        // next 必须指向前面的节点, 这样也排除了环
+        assert!(serde_json::from_str::<SharedVersions<i32>>("[[[1,0]],[0]]").is_err());
+        assert!(serde_json::from_str::<SharedVersions<i32>>("[[[1,1],[2,0]],[1]]").is_err());
+        assert!(serde_json::from_str::<SharedVersions<i32>>("[[[1,null]],[1]]").is_err());
+        assert!(serde_json::from_str::<SharedVersions<i32>>("[[],[]]").unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn shared_versions_long() {
+        // 一条很长的链和它的许多个版本, 读写都不会递归
+        let mut list = persistent::List::new();
+        let mut versions = Vec::new();
+        for i in 0..100_000 {
+            list = list.push_left(i);
+            if i % 1000 == 0 {
+                versions.push(list.clone());
+            }
+        }
+        let json = serde_json::to_string(&SharedVersions(versions)).unwrap();
+        let SharedVersions(back) = serde_json::from_str::<SharedVersions<i32>>(&json).unwrap();
+        assert_eq!(back.len(), 100);
+        assert_eq!(back[99].len(), 99_001);
+        assert!(back[99].pop_left().iter().nth(999).is_some());
+        for pair in back.windows(2) {
+            let mut older = pair[1].clone();
+            for _ in 0..1000 {
+                older = older.pop_left();
+            }
+            assert!(older.ptr_eq(&pair[0]));
+        }
+
+        // 只有尾部的一个节点被用到, 其余的一长串节点在读取时就会被释放
+        let (nodes, _): (Nodes<i32>, serde_json::Value) = serde_json::from_str(&json).unwrap();
+        let only_tail = serde_json::to_string(&(nodes, [Some(0)])).unwrap();
+        let SharedVersions(back) = serde_json::from_str::<SharedVersions<i32>>(&only_tail).unwrap();
+        assert_eq!(back[0].iter().collect::<Vec<_>>(), vec![&0]);
+    }
+
+    #[test]
+    fn long_input() {
+        let json = serde_json::to_string(&(0..200_000).collect::<Vec<i32>>()).unwrap();
+
+        let list: stack::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.len(), 200_000);
+        assert_eq!(serde_json::to_string(&list).unwrap(), json);
+
+        let list: persistent::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.len(), 200_000);
+        assert_eq!(serde_json::to_string(&list).unwrap(), json);
+
+        let list: deque::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.len(), 200_000);
+        assert_eq!(serde_json::to_string(&list).unwrap(), json);
+    }
+}
@@ -0,0 +1,291 @@
+//! [`persistent`](crate::persistent) 和 [`persistent_sync`](crate::persistent_sync) 共用的不可变链表
+//!
+//! 两者只是管理节点的引用计数指针不同, 所以这里的 [`List`] 对指针类型泛型,
+//! 各自通过 [`SharedPointer`] 提供 `Rc` 或 `Arc`, 再用类型别名导出
+
+use crate::dot::{Dot, Edge};
+use crate::layout::Layout;
+use crate::teardown;
+use crate::traits::PersistentStack;
+#[cfg(feature = "serde")]
+use crate::versions::Versioned;
+use std::fmt;
+use std::ops::Deref;
+use std::ptr;
+
+/// 管理节点的引用计数指针, 由 [`RcPointer`](crate::persistent::RcPointer) 和
+/// [`ArcPointer`](crate::persistent_sync::ArcPointer) 实现
+pub trait SharedPointer {
+    type Ptr<N>: Clone + Deref<Target = N>;
+
+    fn new<N>(node: N) -> Self::Ptr<N>;
+
+    fn strong_count<N>(ptr: &Self::Ptr<N>) -> usize;
+
+    /// 只剩这一个引用时取出节点; 否则只放掉这个引用, 返回 `None`
+    fn into_inner<N>(ptr: Self::Ptr<N>) -> Option<N>;
+}
+
+pub(crate) struct Node<T, P: SharedPointer> {
+    elem: T,
+    next: Link<T, P>,
+    // 从这个节点开始到链表末尾的元素个数
+    // 节点不可变, 共享它的所有链表看到的长度都一样, 所以可以缓存在节点上
+    len: usize,
+}
+
+type Link<T, P> = Option<<P as SharedPointer>::Ptr<Node<T, P>>>;
+
+/// 不可变链表, 每次 push/pop 都会返回一个新的版本, 旧版本依然可用
+pub struct List<T, P: SharedPointer> {
+    head: Link<T, P>,
+}
+
+impl<T, P: SharedPointer> List<T, P> {
+    /// 创建一个空链表
+    pub fn new() -> Self {
+        List {
+            head: None,
+        }
+    }
+
+    /// 返回一个在头部添加了 `elem` 的新链表
+    /*
+     当我们push了3次之后, 内存结构如下
+     list1 = None.push_left(A)
+     list2 = list1.push_left(B)
+     list3 = list2.push_left(C)
+
+     [list1] --> (A) <--(B) <-- (C)
+                         ^       ^
+                         |       |
+     [list2] ------------+       |
+                                 |
+                                 |
+     [list3] --------------------+
+     */
+    pub fn push_left(&self, elem: T) -> Self {
+        let node = Node {
+            elem,
+            next: self.head.clone(),
+            len: self.len() + 1,
+        };
+
+        List {
+            head: Some(P::new(node)),
+        }
+    }
+
+    /// 返回一个新链表, 新链表中去掉了原来的第一个元素
+    pub fn pop_left(&self) -> Self {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    /// 元素个数, 直接读头节点上缓存的长度, O(1)
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    /// 链表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// 返回链表头部元素的引用
+    pub fn peek_left(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| {
+            &node.elem
+        })
+    }
+
+    /// 两个链表是否从头节点开始就是同一份, 和 [`Rc::ptr_eq`](std::rc::Rc::ptr_eq) 一样只比较指针
+    ///
+    /// 两个空链表也算相同
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (self.head.as_deref(), other.head.as_deref()) {
+            (Some(a), Some(b)) => ptr::eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, P: SharedPointer> Versioned for List<T, P> {
+    type Elem = T;
+    type Node = Node<T, P>;
+    type Link = P::Ptr<Node<T, P>>;
+
+    fn head(&self) -> Option<&Node<T, P>> {
+        self.head.as_deref()
+    }
+
+    fn next(node: &Node<T, P>) -> Option<&Node<T, P>> {
+        node.next.as_deref()
+    }
+
+    fn elem(node: &Node<T, P>) -> &T {
+        &node.elem
+    }
+
+    fn link(elem: T, next: Link<T, P>) -> Self::Link {
+        let len = next.as_ref().map_or(0, |node| node.len) + 1;
+        P::new(Node { elem, next, len })
+    }
+
+    fn from_head(head: Link<T, P>) -> Self {
+        List { head }
+    }
+}
+
+// 只复制头指针, 新旧两个链表共享所有节点, O(1)
+impl<T, P: SharedPointer> Clone for List<T, P> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T, P: SharedPointer> Default for List<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 和 stack::List 一样, 沿着迭代器输出, 不会递归
+impl<T: fmt::Debug, P: SharedPointer> fmt::Debug for List<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display, P: SharedPointer> fmt::Display for List<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: fmt::Debug, P: SharedPointer> List<T, P> {
+    /// 导出 Graphviz DOT 格式的内存布局图, 可以用 `dot -Tsvg` 渲染
+    ///
+    /// `lists` 是一组 (名字, 链表), 节点上标出 `Rc`/`Arc` 的强引用计数。
+    /// 共享的节点只画一次, 多个链表的箭头会汇合到同一段尾部。
+    /// 其它线程同时在克隆或释放链表时, 计数只是某一时刻的快照
+    pub fn to_dot(lists: &[(&str, &Self)]) -> String {
+        let mut dot = Dot::new();
+        for (label, list) in lists {
+            let mut from = dot.head(label);
+            let mut edge = Edge::Head;
+            let mut cur = list.head.as_ref();
+            while let Some(node) = cur {
+                let (name, fresh) = dot.node(&**node, &node.elem, Some(P::strong_count(node)));
+                dot.edge(&from, &name, edge);
+                // 后面的部分已经画过了
+                if !fresh {
+                    break;
+                }
+                from = name;
+                edge = Edge::Next;
+                cur = node.next.as_ref();
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T: fmt::Display, P: SharedPointer> List<T, P> {
+    /// 画出 ASCII 的内存布局图, 形如 `[list] -> (A, ptr) -> (B, null)`
+    ///
+    /// `lists` 是一组 (名字, 链表), 每个链表画一行。
+    /// 共享的节点只画一次, 后面的链表用 `+` 和 `^` 连到它下面, 和 `persistent` 模块开头手画的图一样
+    pub fn layout(lists: &[(&str, &Self)]) -> String {
+        let mut layout = Layout::new();
+        for (label, list) in lists {
+            layout.list(label);
+            let mut cur = list.head.as_deref();
+            while let Some(node) = cur {
+                if !layout.node(node, &node.elem, node.next.is_some()) {
+                    break;
+                }
+                cur = node.next.as_deref();
+            }
+        }
+        layout.render()
+    }
+}
+
+impl<T, P: SharedPointer> PersistentStack<T> for List<T, P> {
+    fn push_left(&self, elem: T) -> Self {
+        List::push_left(self, elem)
+    }
+
+    fn pop_left(&self) -> Self {
+        List::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        List::peek_left(self)
+    }
+
+    fn len(&self) -> usize {
+        List::len(self)
+    }
+}
+
+// 没有实现IntoIter和IterMut是因为:
+// 我们用了rc, 所有权会被共享, rc指向的东西不可变
+
+/// 按不可变引用迭代, 由 [`List::iter`] 创建
+pub struct Iter<'a, T, P: SharedPointer> {
+    // 保存一个引用, 指向当前要被返回的node
+    next: Option<&'a Node<T, P>>,
+}
+
+impl<T, P: SharedPointer> List<T, P> {
+    /// 从头到尾返回元素的引用
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<'a, T, P: SharedPointer> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+
+    // 剩余元素个数就是下一个节点上缓存的长度
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl<T, P: SharedPointer> ExactSizeIterator for Iter<'_, T, P> {}
+
+impl<T, P: SharedPointer> Drop for List<T, P> {
+    fn drop(&mut self) {
+        // 一直 drop 到第一个被其它链表所引用的节点
+        // 元素析构时 panic 的话, 剩下的节点还挂在 head 上, drop_all 会接着释放
+        teardown::drop_all(&mut self.head, |head| {
+            let mut node = P::into_inner(head.take()?)?;
+            *head = node.next.take();
+            Some(node)
+        });
+    }
+}
@@ -0,0 +1,105 @@
+//! [`stack`](crate::stack) 和 [`deque`](crate::deque) 共用的自底向上归并排序
+//!
+//! 排序时只沿着 next 把节点当成单向链表处理, 不分配内存。
+//! 两种链表的节点类型不同, 各自通过 [`Sortable`] 提供摘节点、合并和放回链表的操作,
+//! 按二进制进位合并 run 的过程以及 panic 时把节点接回去的逻辑都在这里
+
+use std::cmp::Ordering;
+
+pub(crate) trait Sortable {
+    /// 节点的句柄, 比如 `Box<Node<T>>`
+    type Node;
+    type Elem;
+
+    /// 把所有节点按 next 串成一条链拿走, 链表暂时变成空的, 长度不变
+    fn take_nodes(&mut self) -> Option<Self::Node>;
+
+    /// 摘下 `rest` 的第一个节点, `rest` 指向它原来的 next
+    fn split_first(rest: &mut Option<Self::Node>) -> Option<Self::Node>;
+
+    /// 把 `left` 和 `right` 合并到 `merged` 里, 相等时 `left` 在前
+    ///
+    /// 调用 `is_less` 时节点都要挂在这三条链上, 不能只存在于局部变量里
+    fn merge<F: FnMut(&Self::Elem, &Self::Elem) -> bool>(
+        left: &mut Option<Self::Node>,
+        right: &mut Option<Self::Node>,
+        merged: &mut Option<Self::Node>,
+        is_less: &mut F,
+    );
+
+    /// 把这些片段首尾相接, 放回还空着的链表
+    fn restore(&mut self, pieces: impl Iterator<Item = Option<Self::Node>>);
+}
+
+// runs[i] 里是长度为 2^i 的有序链表, 链表长度不可能超过 2^64, 64 格就够了
+const RUNS: usize = usize::BITS as usize;
+
+// 排序过程中节点散落在下面这些链表里, 全都放在这个结构体中,
+// 比较函数 panic 时, 析构函数能找到所有节点, 把它们重新接回 list
+struct Sorter<'a, L: Sortable> {
+    list: &'a mut L,
+    // 还没处理的节点
+    rest: Option<L::Node>,
+    runs: [Option<L::Node>; RUNS],
+    // 正在合并的两个链表和合并的结果
+    left: Option<L::Node>,
+    right: Option<L::Node>,
+    merged: Option<L::Node>,
+}
+
+impl<L: Sortable> Sorter<'_, L> {
+    fn merge(&mut self, is_less: &mut impl FnMut(&L::Elem, &L::Elem) -> bool) {
+        L::merge(&mut self.left, &mut self.right, &mut self.merged, is_less);
+    }
+}
+
+impl<L: Sortable> Drop for Sorter<'_, L> {
+    fn drop(&mut self) {
+        // 正常结束时只有 merged 不为空; panic 时把所有片段首尾相接, 长度不变
+        let Sorter { list, rest, runs, left, right, merged } = self;
+        let pieces = [merged, left, right].into_iter().chain(runs).chain([rest]);
+        list.restore(pieces.map(Option::take));
+    }
+}
+
+/// 稳定排序, O(n log n)
+pub(crate) fn sort_by<L, F>(list: &mut L, mut compare: F)
+where
+    L: Sortable,
+    F: FnMut(&L::Elem, &L::Elem) -> Ordering,
+{
+    let mut is_less = |a: &L::Elem, b: &L::Elem| compare(a, b) == Ordering::Less;
+    let mut sorter = Sorter {
+        rest: list.take_nodes(),
+        runs: std::array::from_fn(|_| None),
+        left: None,
+        right: None,
+        merged: None,
+        list,
+    };
+
+    // 像二进制加一那样: 每次拿一个节点作为长度为 1 的 run,
+    // 遇到同样长度的 run 就合并, 进位到下一格
+    while let Some(node) = L::split_first(&mut sorter.rest) {
+        sorter.merged = Some(node);
+        let mut i = 0;
+        while sorter.runs[i].is_some() {
+            // runs[i] 里的元素都排在当前 run 前面, 放在 left 才能保证稳定
+            sorter.left = sorter.runs[i].take();
+            sorter.right = sorter.merged.take();
+            sorter.merge(&mut is_less);
+            i += 1;
+        }
+        sorter.runs[i] = sorter.merged.take();
+    }
+
+    // 下标越大的 run 里的元素越靠前, 从小往大合并
+    for i in 0..RUNS {
+        if sorter.runs[i].is_some() {
+            sorter.left = sorter.runs[i].take();
+            sorter.right = sorter.merged.take();
+            sorter.merge(&mut is_less);
+        }
+    }
+    // sorter 析构时把 merged 放回链表
+}
@@ -0,0 +1,1242 @@
+//! 单向链表实现的栈 (对应 `src/bin/list6.rs`)
+//!
+//! 内存布局:
+//! ```text
+//! [ptr] -> (1, ptr) -> (2, null)
+//! ```
+//!
+//! 默认每次 push 都 `Box::new` 一个节点, pop 时释放。用 [`List::with_capacity`] 或
+//! [`List::reserve`] 预留容量之后, pop 出来的节点只要不超过容量就不释放, 放进节点池留给之后的 push,
+//! 反复 push/pop 时就不用每次都找分配器要内存了
+
+use crate::dot::{Dot, Edge};
+use crate::layout::Layout;
+use crate::range::to_indices;
+use crate::sort::{self, Sortable};
+use crate::teardown;
+use crate::traits::Stack;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::MaybeUninit;
+use std::ops::RangeBounds;
+
+pub(crate) struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+/// 只能在头部(左端)进行操作的单向链表栈
+pub struct List<T> {
+    head: Link<T>,
+    // 元素个数, push/pop 时顺手维护, 不用遍历就能知道长度
+    len: usize,
+    // 空闲节点池, 里面的节点都没有初始化
+    pool: Vec<Box<MaybeUninit<Node<T>>>>,
+    // pop 时链表里的节点加上池子里的节点不超过 cap, 才把节点留下, 为 0 时直接释放
+    cap: usize,
+}
+
+impl<T> List<T> {
+    /// 创建一个空链表
+    pub fn new() -> Self {
+        List {
+            head: None,
+            len: 0,
+            pool: Vec::new(),
+            cap: 0,
+        }
+    }
+
+    /// 创建一个空链表, 并预先分配 `capacity` 个节点
+    ///
+    /// 之后 pop 时, 只要链表里的节点加上池子里的空闲节点不超过 `capacity`, 节点就留在池子里,
+    /// 否则直接释放。链表比 `capacity` 长时多出来的节点照常分配和释放
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut list = Self::new();
+        list.reserve(capacity);
+        list
+    }
+
+    /// 不用再分配就能容纳的元素个数, 也就是链表的长度加上池子里空闲节点的个数
+    pub fn capacity(&self) -> usize {
+        self.len + self.pool.len()
+    }
+
+    /// 保证至少还能再 push `additional` 个元素而不用分配节点, 容量至少变成 `len + additional`
+    ///
+    /// # Panics
+    ///
+    /// 容量超过 `usize::MAX` 时 panic
+    pub fn reserve(&mut self, additional: usize) {
+        let wanted = self.len.checked_add(additional).expect("capacity overflow");
+        self.cap = self.cap.max(wanted);
+        let missing = additional.saturating_sub(self.pool.len());
+        self.pool.reserve_exact(missing);
+        for _ in 0..missing {
+            self.pool.push(Box::new(MaybeUninit::uninit()));
+        }
+    }
+
+    /// 释放池子里所有的空闲节点, 容量降到和长度相同
+    ///
+    /// 和 `Vec::shrink_to_fit` 一样, 之后 pop 出来的节点还会留下, 但总数不超过现在的长度;
+    /// 对空链表调用就回到 [`List::new`] 的状态, 不再回收节点
+    pub fn shrink_to_fit(&mut self) {
+        self.pool = Vec::new();
+        self.cap = self.len;
+    }
+
+    /// 元素个数, O(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 链表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 在链表头部添加节点
+    pub fn push_left(&mut self, value: T) {
+        // 构造新节点
+        let node = Node {
+            elem: value,
+            // next指向原来head指向的节点
+            // head现在是None
+            next: self.head.take(),
+        };
+        // 池子里有空闲节点就直接用, 否则再分配一个
+        let boxed = match self.pool.pop() {
+            Some(slot) => Box::write(slot, node),
+            None => Box::new(node),
+        };
+        // 让链表头部指向新节点
+        self.head = Some(boxed);
+        self.len += 1;
+    }
+
+    /// 从链表头部移出元素
+    pub fn pop_left(&mut self) -> Option<T> {
+        self.head.take().map(|boxed| {
+            self.len -= 1;
+            let node = self.recycle(boxed);
+            // 这里node.next 指向的Link 所有权转移给self.head了
+            // 既让self.head指向node的下一个元素, 又让node指向下一个元素的引用断掉了
+            self.head = node.next;
+            node.elem
+        })
+    }
+
+    // 把节点的内容移出来, 没超过容量就把空出来的内存留在池子里, 否则释放
+    // 调用时 len 已经不算这个节点了
+    fn recycle(&mut self, boxed: Box<Node<T>>) -> Node<T> {
+        let raw = Box::into_raw(boxed);
+        // SAFETY: raw 来自 Box::into_raw, 指向初始化好的节点。内容读出来之后,
+        // 这块内存只当作布局相同的 MaybeUninit<Node<T>> 使用, 不会再被当成节点析构
+        let (node, slot) = unsafe { (raw.read(), Box::from_raw(raw.cast::<MaybeUninit<Node<T>>>())) };
+        if self.len + self.pool.len() < self.cap {
+            self.pool.push(slot);
+        }
+        node
+    }
+
+    /// 返回链表头部元素的引用
+    pub fn peek_left(&self) -> Option<&T> {
+        let head = self.head.as_ref();
+        head.map(|node| {
+            &node.elem
+        })
+    }
+
+    /// 返回链表头部元素的可变引用
+    pub fn peek_left_mut(&mut self) -> Option<&mut T> {
+        let head = self.head.as_mut();
+        head.map(|node| {
+            &mut node.elem
+        })
+    }
+
+    /// 原地反转链表, 只改节点之间的指向, 不会重新分配节点
+    pub fn reverse(&mut self) {
+        // prev 是已经反转好的部分, cur 是还没处理的部分
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = prev;
+            prev = Some(node);
+        }
+        self.head = prev;
+    }
+
+    /// 把 `other` 的所有节点接到末尾, `other` 变为空
+    ///
+    /// 节点直接挂过去, 不会重新分配, 但要先走到末尾, 所以是 O(self.len())
+    pub fn append(&mut self, other: &mut Self) {
+        *self.tail_link() = other.head.take();
+        self.len += std::mem::take(&mut other.len);
+    }
+
+    /// 从下标 `at` 处断开, 返回 `[at, len)` 部分, 自己只保留 `[0, at)`
+    ///
+    /// # Panics
+    ///
+    /// `at > len` 时 panic
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        let mut cur = &mut self.head;
+        for _ in 0..at {
+            cur = &mut cur.as_mut().unwrap().next;
+        }
+        let rest = List {
+            head: cur.take(),
+            len: self.len - at,
+            pool: Vec::new(),
+            cap: 0,
+        };
+        self.len = at;
+        rest
+    }
+
+    /// 稳定排序, 见 [`List::sort_by`]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp)
+    }
+
+    /// 按 `f` 算出的 key 稳定排序, 见 [`List::sort_by`]
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// 用自底向上的归并排序原地排序, 稳定, O(n log n)
+    ///
+    /// 只改节点之间的指向, 不分配内存。
+    /// `compare` panic 时所有节点都会接回链表里, 链表依然可用, 只是顺序不确定
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        sort::sort_by(self, compare)
+    }
+
+    /// 只保留 `f` 返回 `true` 的元素, 其余的节点从链表上摘下来释放
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|elem| f(elem))
+    }
+
+    /// 同 [`List::retain`], 但 `f` 可以修改元素
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        for _ in self.extract_if(|elem| !f(elem)) {}
+    }
+
+    /// 返回一个迭代器, 从头到尾检查每个元素, 把 `pred` 返回 `true` 的节点摘下来并返回元素
+    ///
+    /// 是惰性的: 只有迭代到的部分会被检查, 迭代器中途被丢掉时后面的元素原样留在链表里
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(&mut self, pred: F) -> ExtractIf<'_, T, F> {
+        ExtractIf {
+            link: Some(&mut self.head),
+            len: &mut self.len,
+            pred,
+        }
+    }
+
+    /// 移出下标在 `range` 内的元素, 返回按顺序产出它们的迭代器
+    ///
+    /// 每次 next 摘下一个节点; 迭代器被丢掉时, 范围内剩下的元素也会被移除
+    ///
+    /// # Panics
+    ///
+    /// 起点大于终点, 或者终点超过链表长度时 panic
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let (start, end) = to_indices(range, self.len);
+        let mut link = &mut self.head;
+        for _ in 0..start {
+            link = &mut link.as_mut().unwrap().next;
+        }
+        Drain {
+            link,
+            len: &mut self.len,
+            remaining: end - start,
+        }
+    }
+
+    // 返回最后一个节点的 next, 也就是链表末尾那个 None 所在的位置
+    // 链表为空时就是 head 本身
+    fn tail_link(&mut self) -> &mut Link<T> {
+        let mut cur = &mut self.head;
+        // 这里不能写成 while let Some(node) = cur,
+        // 那样循环结束后 cur 还被借用着, 没法返回
+        while cur.is_some() {
+            cur = &mut cur.as_mut().unwrap().next;
+        }
+        cur
+    }
+}
+
+impl<T> Sortable for List<T> {
+    type Node = Box<Node<T>>;
+    type Elem = T;
+
+    fn take_nodes(&mut self) -> Link<T> {
+        self.head.take()
+    }
+
+    fn split_first(rest: &mut Link<T>) -> Link<T> {
+        rest.take().map(|mut node| {
+            *rest = node.next.take();
+            node
+        })
+    }
+
+    fn merge<F: FnMut(&T, &T) -> bool>(
+        left: &mut Link<T>,
+        right: &mut Link<T>,
+        merged: &mut Link<T>,
+        is_less: &mut F,
+    ) {
+        let mut tail = merged;
+        loop {
+            let take_right = match (&*left, &*right) {
+                (Some(l), Some(r)) => is_less(&r.elem, &l.elem),
+                (Some(_), None) => {
+                    *tail = left.take();
+                    return;
+                }
+                (None, _) => {
+                    *tail = right.take();
+                    return;
+                }
+            };
+            let from = if take_right { &mut *right } else { &mut *left };
+            let mut node = from.take().unwrap();
+            *from = node.next.take();
+            tail = &mut tail.insert(node).next;
+        }
+    }
+
+    fn restore(&mut self, pieces: impl Iterator<Item = Link<T>>) {
+        let mut tail = &mut self.head;
+        for piece in pieces {
+            while tail.is_some() {
+                tail = &mut tail.as_mut().unwrap().next;
+            }
+            *tail = piece;
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 不用 derive(Debug): derive 出来的实现会顺着 next 一层层递归,
+// 输出成 Some(Node { elem: .., next: Some(..) }) 这样, 长链表还会爆栈
+// 手写的版本用迭代器逐个输出, 形如 [3, 2, 1], 也支持 {:#?}
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: fmt::Debug> List<T> {
+    /// 导出 Graphviz DOT 格式的内存布局图, 可以用 `dot -Tsvg` 渲染
+    ///
+    /// `lists` 是一组 (名字, 链表), 每个链表画成一个句柄, 沿着 next 画出它的节点
+    pub fn to_dot(lists: &[(&str, &Self)]) -> String {
+        let mut dot = Dot::new();
+        for (label, list) in lists {
+            let mut from = dot.head(label);
+            let mut edge = Edge::Head;
+            let mut cur = list.head.as_deref();
+            while let Some(node) = cur {
+                let (name, fresh) = dot.node(node, &node.elem, None);
+                dot.edge(&from, &name, edge);
+                // Box 的节点不会被两个链表共享, 只有同一个链表传了两次时才会遇到画过的节点
+                if !fresh {
+                    break;
+                }
+                from = name;
+                edge = Edge::Next;
+                cur = node.next.as_deref();
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T: fmt::Display> List<T> {
+    /// 画出 ASCII 的内存布局图, 形如 `[ptr] -> (1, ptr) -> (2, null)`
+    ///
+    /// `lists` 是一组 (名字, 链表), 每个链表画一行, 名字画在表示栈上链表头的 `[]` 里
+    pub fn layout(lists: &[(&str, &Self)]) -> String {
+        let mut layout = Layout::new();
+        for (label, list) in lists {
+            layout.list(label);
+            let mut cur = list.head.as_deref();
+            while let Some(node) = cur {
+                if !layout.node(node, &node.elem, node.next.is_some()) {
+                    break;
+                }
+                cur = node.next.as_deref();
+            }
+        }
+        layout.render()
+    }
+}
+
+// 把 iter 里的元素依次挂到 tail 指向的位置, 保持原来的顺序, 返回挂上去的个数
+fn append_iter<T>(mut tail: &mut Link<T>, iter: impl IntoIterator<Item = T>) -> usize {
+    let mut n = 0;
+    for elem in iter {
+        let node = tail.insert(Box::new(Node { elem, next: None }));
+        tail = &mut node.next;
+        n += 1;
+    }
+    n
+}
+
+// 和 LinkedList 一样, 第一个元素在头部, 也就是 pop_left 最先拿到它
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.len = append_iter(&mut list.head, iter);
+        list
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for List<T> {
+    fn from(arr: [T; N]) -> Self {
+        Self::from_iter(arr)
+    }
+}
+
+// 和 LinkedList 一样追加到尾部, 单向链表要先走到末尾, 所以是 O(len)
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let n = append_iter(self.tail_link(), iter);
+        self.len += n;
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for List<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// 下面几个 trait 都是基于迭代器实现的, 不会沿着 next 递归
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+// 字典序, 和 Vec/LinkedList 一致
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+// 先写入长度, 否则 [[1], [2]] 和 [[1, 2]] 这样嵌套的链表会得到相同的哈希
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> Stack<T> for List<T> {
+    type Ref<'a> = &'a T where T: 'a;
+    type RefMut<'a> = &'a mut T where T: 'a;
+
+    fn push_left(&mut self, elem: T) {
+        List::push_left(self, elem)
+    }
+
+    fn pop_left(&mut self) -> Option<T> {
+        List::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        List::peek_left(self)
+    }
+
+    fn peek_left_mut(&mut self) -> Option<&mut T> {
+        List::peek_left_mut(self)
+    }
+
+    fn len(&self) -> usize {
+        List::len(self)
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // 每次先把节点的 next 接回 head 再 drop 这个节点,
+        // 它的 `next` 已经是 None, 因此这里并不会发生递归drop
+        // 元素析构时 panic 的话, 剩下的节点还挂在 head 上, drop_all 会接着释放
+        teardown::drop_all(&mut self.head, |head| {
+            head.take().map(|mut boxed_node| {
+                *head = boxed_node.next.take();
+                boxed_node
+            })
+        });
+    }
+}
+
+
+// 为List实现3种迭代器
+
+/// 按值迭代, 由 [`List::into_iter`](IntoIterator::into_iter) 创建
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        // 直接转移所有权
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        // access fields of a tuple struct numerically
+        self.0.pop_left()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+/// 按不可变引用迭代, 由 [`List::iter`] 创建
+// 在结构体内使用生命周期
+// 代表着 被引用的这个东西 至少要和 结构体对象的实例 活的一样长
+pub struct Iter<'a, T> {
+    // 保存一个引用, 指向当前要被返回的node
+    next: Option<&'a Node<T>>,
+    // 还剩多少个元素没有返回
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的引用
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            // self.head的类型是 Option<Box<Node<T>>>
+            // as_deref相当于对Box<Node<T>>进行了一次 Deref trait 的操作
+            // 所以 Option<Box<Node<T>>> -> Option<&Node<T>>
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// 按可变引用迭代, 由 [`List::iter_mut`] 创建
+pub struct IterMut<'a, T> {
+    // 保存一个引用, 指向当前要被返回的node
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的可变引用
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        // 可变引用没有实现copy trait, 所以要先take出来再map
+        self.next.take().map(|node| {
+            self.next = node.next.as_deref_mut();
+            self.len -= 1;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// 边迭代边摘掉满足条件的节点, 由 [`List::extract_if`] 创建
+pub struct ExtractIf<'a, T, F> {
+    // 指向下一个要检查的节点的那个 Link, 摘掉节点时直接改它
+    link: Option<&'a mut Link<T>>,
+    len: &'a mut usize,
+    pred: F,
+}
+
+impl<T, F: FnMut(&mut T) -> bool> Iterator for ExtractIf<'_, T, F> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let link = self.link.take()?;
+            let extract = match link.as_mut() {
+                Some(node) => (self.pred)(&mut node.elem),
+                None => return None,
+            };
+            if !extract {
+                // 和 tail_link 一样, 判断完再重新借用一次, 才能把借用存下来
+                self.link = Some(&mut link.as_mut().unwrap().next);
+                continue;
+            }
+            let mut node = link.take().unwrap();
+            *link = node.next.take();
+            *self.len -= 1;
+            self.link = Some(link);
+            return Some(node.elem);
+        }
+    }
+}
+
+/// 移出一段连续的元素, 由 [`List::drain`] 创建
+pub struct Drain<'a, T> {
+    // 指向下一个要移出的节点的那个 Link
+    link: &'a mut Link<T>,
+    len: &'a mut usize,
+    remaining: usize,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        *self.len -= 1;
+        let mut node = self.link.take().unwrap();
+        *self.link = node.next.take();
+        Some(node.elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+// 没迭代完的部分也要移除
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn test_iter_mut() {
+        let mut list = List::new();
+        list.push_left(1);
+        list.push_left(2);
+
+        let mut it = list.iter_mut();
+        assert_eq!(it.next(), Some(&mut 2));
+        assert_eq!(it.next(), Some(&mut 1));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut list = List::new();
+        list.push_left(1);
+        list.push_left(2);
+
+        let mut it = list.iter();
+        assert_eq!(it.next(), Some(&2));
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut list = List::new();
+        list.push_left(1);
+        list.push_left(2);
+
+        let mut it = list.into_iter();
+        assert_eq!(it.next(), Some(2));
+        assert_eq!(it.next(), Some(1));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut list = List::new();
+        list.push_left(1);
+        list.push_left(2);
+
+        assert_eq!(list.peek_left(), Some(&2));
+        list.pop_left();
+        assert_eq!(list.peek_left_mut(), Some(&mut 1));
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(list.iter_mut().skip(1).len(), 2);
+
+        list.pop_left();
+        assert_eq!(list.len(), 2);
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(iter.len(), 1);
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_left(), None);
+
+        // Populate list
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(3));
+        assert_eq!(list.pop_left(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_left(4);
+        list.push_left(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_left(), Some(5));
+        assert_eq!(list.pop_left(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_left(), Some(1));
+        assert_eq!(list.pop_left(), None);
+    }
+
+    #[test]
+    fn fmt() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        assert_eq!(list.to_string(), "[]");
+
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+        assert_eq!(format!("{:?}", list), "[3, 2, 1]");
+        assert_eq!(format!("{:#?}", list), "[\n    3,\n    2,\n    1,\n]");
+        assert_eq!(list.to_string(), "[3, 2, 1]");
+        // 格式参数会传给每个元素
+        assert_eq!(format!("{:02}", list), "[03, 02, 01]");
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list = List::from([1, 2, 3]);
+        let addrs: Vec<*const i32> = list.iter().map(|e| e as *const i32).collect();
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert_eq!(list.len(), 3);
+        // 节点还是原来那些
+        let rev: Vec<*const i32> = list.iter().map(|e| e as *const i32).collect();
+        assert_eq!(rev, addrs.into_iter().rev().collect::<Vec<_>>());
+
+        let mut empty = List::<i32>::new();
+        empty.reverse();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn append_split_off() {
+        let mut a = List::from([1, 2]);
+        let mut b = List::from([3, 4, 5]);
+        let third = b.peek_left().unwrap() as *const i32;
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(a, List::from([1, 2, 3, 4, 5]));
+        assert_eq!(a.len(), 5);
+        assert_eq!(a.iter().nth(2).unwrap() as *const i32, third);
+
+        let mut empty = List::new();
+        empty.append(&mut a);
+        assert_eq!(empty.len(), 5);
+        assert!(a.is_empty());
+
+        let mut list = empty;
+        let rest = list.split_off(2);
+        assert_eq!(list, List::from([1, 2]));
+        assert_eq!(rest, List::from([3, 4, 5]));
+        assert_eq!(rest.peek_left().unwrap() as *const i32, third);
+
+        let mut all = List::from([1, 2]);
+        assert_eq!(all.split_off(0), List::from([1, 2]));
+        assert!(all.is_empty());
+        let mut list = List::from([1, 2]);
+        assert!(list.split_off(2).is_empty());
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        List::from([1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn sort() {
+        for n in 0..20 {
+            // 乱序, 且有重复
+            let mut list: List<i32> = (0..n).map(|i| (i * 7) % 5).collect();
+            let mut expected: Vec<i32> = list.iter().copied().collect();
+            expected.sort();
+            list.sort();
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+            assert_eq!(list.len(), n as usize);
+        }
+
+        let mut list = List::from([3, 1, 2]);
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(list, List::from([3, 2, 1]));
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let pairs: Vec<(i32, usize)> = (0..100).map(|i| ((i * 37 % 10) as i32, i)).collect();
+        let mut list: List<(i32, usize)> = pairs.iter().copied().collect();
+        list.sort_by_key(|&(key, _)| key);
+        let mut expected = pairs;
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn sort_panic_keeps_all_nodes() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        for limit in [0, 1, 5, 20, 50] {
+            let mut list: List<i32> = (0..30).rev().collect();
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                list.sort_by(|a, b| {
+                    calls += 1;
+                    if calls > limit {
+                        panic!("comparator panicked");
+                    }
+                    a.cmp(b)
+                })
+            }));
+            assert!(result.is_err());
+            assert_eq!(list.len(), 30);
+            let mut elems: Vec<i32> = list.iter().copied().collect();
+            elems.sort();
+            assert_eq!(elems, (0..30).collect::<Vec<_>>());
+            // 链表还能正常使用
+            list.sort();
+            assert_eq!(list.peek_left(), Some(&0));
+        }
+    }
+
+    #[test]
+    fn retain() {
+        let mut list: List<i32> = (0..10).collect();
+        list.retain(|&x| x % 3 != 0);
+        assert_eq!(list, List::from([1, 2, 4, 5, 7, 8]));
+        assert_eq!(list.len(), 6);
+
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x > 40
+        });
+        assert_eq!(list, List::from([50, 70, 80]));
+
+        list.retain(|_| false);
+        assert!(list.is_empty());
+        assert_eq!(list.peek_left(), None);
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut list: List<i32> = (0..10).collect();
+        let addr = list.iter().nth(5).unwrap() as *const i32;
+        let evens: Vec<i32> = list.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+        assert_eq!(list, List::from([1, 3, 5, 7, 9]));
+        assert_eq!(list.len(), 5);
+        // 留下的节点没有被重新分配
+        assert_eq!(list.iter().nth(2).unwrap() as *const i32, addr);
+
+        // 惰性: 只摘掉迭代到的部分
+        assert_eq!(list.extract_if(|x| *x > 1).next(), Some(3));
+        assert_eq!(list, List::from([1, 5, 7, 9]));
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn drain() {
+        let mut list: List<i32> = (0..10).collect();
+        let mut drain = list.drain(2..5);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some(2));
+        // 没迭代完的也会被移除
+        drop(drain);
+        assert_eq!(list, List::from([0, 1, 5, 6, 7, 8, 9]));
+        assert_eq!(list.len(), 7);
+
+        assert_eq!(list.drain(5..=6).collect::<Vec<_>>(), vec![8, 9]);
+        assert_eq!(list.drain(..1).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(list.drain(2..2).count(), 0);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), vec![1, 5, 6, 7]);
+        assert!(list.is_empty());
+        assert_eq!(list.drain(..).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        List::from([1, 2]).drain(1..3);
+    }
+
+    #[test]
+    fn for_loops() {
+        let mut list = List::from([1, 2, 3]);
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        let mut seen = Vec::new();
+        for elem in &list {
+            seen.push(*elem);
+        }
+        assert_eq!(seen, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn from_iter_extend() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_left(), Some(&1));
+
+        list.extend(vec![4, 5]);
+        list.extend(&[6]);
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
+
+        let mut empty = List::new();
+        empty.extend([7, 8]);
+        assert_eq!(empty, List::from([7, 8]));
+        empty.extend(None::<i32>);
+        assert_eq!(empty.len(), 2);
+    }
+
+    #[test]
+    fn clone_cmp_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<T: Hash>(t: &T) -> u64 {
+            let mut h = DefaultHasher::new();
+            t.hash(&mut h);
+            h.finish()
+        }
+
+        let a = List::from([1, 2, 3]);
+        let mut b = a.clone();
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        *b.peek_left_mut().unwrap() = 0;
+        assert_ne!(a, b);
+        assert!(b < a);
+        assert!(List::from([1, 2]) < a);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+        assert!(List::<f64>::from([f64::NAN]).partial_cmp(&List::from([1.0])).is_none());
+
+        let nested1 = List::from([List::from([1]), List::from([2])]);
+        let nested2 = List::from([List::from([1, 2]), List::new()]);
+        assert_ne!(hash_of(&nested1), hash_of(&nested2));
+    }
+
+    // 如果是默认的Drop实现, 这个测试是无法通过的
+    #[test]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list.push_left(i.to_string());
+        }
+        // 派生的 Debug 会递归, 在这里爆栈
+        assert!(format!("{:?}", list).starts_with("[\"99999\", \"99998\""));
+        let copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(copy.cmp(&list), std::cmp::Ordering::Equal);
+        drop(copy);
+        list.sort();
+        assert_eq!(list.peek_left().map(String::as_str), Some("0"));
+        drop(list);
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list: List<_> = tracker.elems(0..100).into_iter().collect();
+            for _ in 0..10 {
+                drop(list.pop_left());
+            }
+            list.push_left(tracker.elem(100));
+            list.peek_left_mut().unwrap().value += 1;
+            assert_eq!(list.iter().count(), 91);
+
+            let copy = list.clone();
+            let mut tail = list.split_off(50);
+            list.append(&mut tail);
+            list.reverse();
+            list.sort();
+            list.retain(|x| x.value % 2 == 0);
+            drop(list.extract_if(|x| x.value % 3 == 0).next());
+            drop(list.drain(5..20));
+
+            // 只迭代一部分就丢掉
+            let mut iter = copy.into_iter();
+            drop(iter.next());
+            drop(iter);
+            drop(list);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, quiet_panic, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list: List<_> = tracker.elems(0..10).into_iter().collect();
+            list.push_left(tracker.panicking_elem(10));
+            list.extend(tracker.elems(11..20));
+            // 析构到一半 panic, 剩下的节点照样释放
+            assert!(panics(|| drop(list)));
+            tracker.assert_all_dropped_once();
+
+            let mut list: List<_> = tracker.elems(0..30).into_iter().collect();
+            let mut calls = 0;
+            assert!(panics(|| list.sort_by(|a, b| {
+                calls += 1;
+                if calls > 20 {
+                    quiet_panic();
+                }
+                a.cmp(b)
+            })));
+            assert!(panics(|| list.retain(|x| x.value < 25 || quiet_panic())));
+            assert_eq!(list.len(), 30);
+            drop(list);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    // 析构到一半 panic 时, 剩下的节点不能落到局部变量里被递归析构, 否则长链表会爆栈
+    // miri 下跑十万个节点太慢了
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn panic_in_elem_drop_long_list() {
+        use crate::testkit::{panics, Tracker};
+
+        let tracker = Tracker::new();
+        let mut list: List<_> = tracker.elems(0..99_990).into_iter().collect();
+        list.push_left(tracker.panicking_elem(99_990));
+        list.extend(tracker.elems(99_991..100_000));
+        assert!(panics(|| drop(list)));
+        assert_eq!(tracker.dropped(), 100_000);
+        tracker.assert_all_dropped_once();
+    }
+
+    #[test]
+    fn pool_reuses_nodes() {
+        use crate::testkit::{assert_no_leaks, counts};
+
+        assert_no_leaks(|| {
+            let mut list = List::with_capacity(10);
+            assert_eq!(list.capacity(), 10);
+            // 预留的节点够用, push/pop 都不会再分配或释放
+            let before = counts();
+            for round in 0..3 {
+                for i in 0..10 {
+                    list.push_left(round * 10 + i);
+                }
+                assert_eq!(list.capacity(), 10);
+                for i in (0..10).rev() {
+                    assert_eq!(list.pop_left(), Some(round * 10 + i));
+                }
+            }
+            assert_eq!(counts(), before);
+
+            // 超出容量的部分照常分配, pop 时池子满了就释放
+            for i in 0..15 {
+                list.push_left(i);
+            }
+            assert_eq!(list.capacity(), 15);
+            while list.pop_left().is_some() {}
+            assert_eq!(list.capacity(), 10);
+
+            list.reserve(4);
+            assert_eq!(list.capacity(), 10);
+            list.push_left(0);
+            list.reserve(12);
+            assert_eq!(list.capacity(), 13);
+
+            // 和 Vec 一样, shrink_to_fit 之后容量就是当前的长度
+            list.shrink_to_fit();
+            assert_eq!(list.capacity(), 1);
+            list.pop_left();
+            assert_eq!(list.capacity(), 1);
+            list.shrink_to_fit();
+            assert_eq!(list.capacity(), 0);
+            list.push_left(1);
+            list.pop_left();
+            assert_eq!(list.capacity(), 0);
+        });
+
+        // 链表比容量长时, 多出来的节点 pop 时直接释放
+        let mut list = List::with_capacity(2);
+        for i in 0..1000 {
+            list.push_left(i);
+        }
+        list.pop_left();
+        assert_eq!(list.capacity(), 999);
+        while list.len() > 1 {
+            list.pop_left();
+        }
+        assert_eq!(list.capacity(), 2);
+
+        // 没有预留容量时不回收节点
+        let mut list = List::new();
+        list.push_left(1);
+        list.pop_left();
+        assert_eq!(list.capacity(), 0);
+    }
+
+    #[test]
+    fn pooled_drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::with_capacity(8);
+            for elem in tracker.elems(0..6) {
+                list.push_left(elem);
+            }
+            drop(list.pop_left());
+            drop(list.pop_left());
+            assert_eq!(tracker.dropped(), 2);
+            list.push_left(tracker.elem(6));
+            // 池子里的空闲节点没有元素, 释放时不会析构任何东西
+            list.shrink_to_fit();
+            list.reserve(5);
+            drop(list);
+            tracker.assert_all_dropped_once();
+        });
+    }
+}
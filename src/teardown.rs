@@ -0,0 +1,55 @@
+//! 链表析构时逐个释放节点的公共逻辑
+//!
+//! 析构函数里用循环代替递归, 长链表才不会爆栈; 但元素的析构函数可能 panic,
+//! 这时循环被打断, 剩下的节点要么泄漏(裸指针、`Rc` 循环), 要么落到一个局部变量里被递归析构。
+//! [`drop_all`] 在每次析构之前放一个 guard, unwind 时由它接着把剩下的节点释放完
+
+use std::marker::PhantomData;
+use std::mem;
+
+/// 反复调用 `pop` 从 `state` 上摘下一项(元素或者节点)并析构, 直到返回 `None`
+///
+/// 某一项析构时 panic 的话, 会在 unwind 的过程中继续 pop 并析构剩下的项,
+/// 如果其中又有一项 panic, 进程会直接 abort
+pub(crate) fn drop_all<S, T, F>(state: &mut S, mut pop: F)
+where
+    F: FnMut(&mut S) -> Option<T>,
+{
+    struct Guard<'a, S, T, F: FnMut(&mut S) -> Option<T>> {
+        state: &'a mut S,
+        pop: &'a mut F,
+        _item: PhantomData<T>,
+    }
+
+    impl<S, T, F: FnMut(&mut S) -> Option<T>> Drop for Guard<'_, S, T, F> {
+        fn drop(&mut self) {
+            while (self.pop)(self.state).is_some() {}
+        }
+    }
+
+    while let Some(item) = pop(state) {
+        let guard = Guard {
+            state: &mut *state,
+            pop: &mut pop,
+            _item: PhantomData,
+        };
+        drop(item);
+        mem::forget(guard);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::drop_all;
+    use crate::testkit::{panics, Tracker};
+
+    #[test]
+    fn keeps_dropping_after_panic() {
+        let tracker = Tracker::new();
+        let mut elems = tracker.elems(0..5);
+        elems.insert(2, tracker.panicking_elem(5));
+        assert!(panics(|| drop_all(&mut elems, Vec::pop)));
+        assert!(elems.is_empty());
+        tracker.assert_all_dropped_once();
+    }
+}
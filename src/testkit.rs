@@ -0,0 +1,247 @@
+//! 测试用的内存和析构检查工具, 只在 `cfg(test)` 下编译
+//!
+//! - 全局分配器换成 [`Counting`], 按线程统计分配和释放的次数,
+//!   并行跑的测试互不干扰, [`assert_no_leaks`] 检查一段代码前后两者是否相等
+//! - [`Tracker`] 创建的 [`DropCounter`] 元素会记录自己被析构了几次,
+//!   [`Tracker::assert_all_dropped_once`] 检查每个元素都恰好析构了一次
+//!
+//! 析构时 panic 的元素用 `resume_unwind` 抛出, 不经过 panic hook,
+//! 这样 hook 打印信息时的分配不会被算进来
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::fmt;
+use std::panic;
+use std::rc::Rc;
+
+pub(crate) struct Counting;
+
+thread_local! {
+    // (分配次数, 释放次数)
+    // 常量初始化、没有析构函数, 在分配器里访问不会再分配内存
+    static COUNTS: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+fn bump(alloc: usize, dealloc: usize) {
+    // 线程退出时 thread_local 可能已经销毁, 这时不再统计
+    let _ = COUNTS.try_with(|counts| {
+        let (a, d) = counts.get();
+        counts.set((a + alloc, d + dealloc));
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            bump(1, 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            bump(1, 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        bump(0, 1);
+    }
+
+    // realloc 只是换了一块内存, 不影响次数
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// 当前线程到目前为止的 (分配次数, 释放次数)
+pub(crate) fn counts() -> (usize, usize) {
+    COUNTS.with(Cell::get)
+}
+
+/// 运行 `f`, 检查它分配的内存都释放了
+pub(crate) fn assert_no_leaks<F: FnOnce()>(f: F) {
+    let (alloc_before, dealloc_before) = counts();
+    f();
+    let (alloc_after, dealloc_after) = counts();
+    let allocs = alloc_after - alloc_before;
+    let deallocs = dealloc_after - dealloc_before;
+    assert_eq!(allocs, deallocs, "{allocs} allocations but {deallocs} deallocations");
+}
+
+/// 运行 `f`, 返回它是否 panic 了
+pub(crate) fn panics<F: FnOnce()>(f: F) -> bool {
+    panic::catch_unwind(panic::AssertUnwindSafe(f)).is_err()
+}
+
+/// 不经过 panic hook 直接开始 unwind
+pub(crate) fn quiet_panic() -> ! {
+    panic::resume_unwind(Box::new("quiet panic"))
+}
+
+/// 创建 [`DropCounter`] 并记录它们的析构次数
+#[derive(Default)]
+pub(crate) struct Tracker {
+    drops: Rc<RefCell<Vec<usize>>>,
+}
+
+impl Tracker {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn elem(&self, value: i32) -> DropCounter {
+        let mut drops = self.drops.borrow_mut();
+        drops.push(0);
+        DropCounter {
+            value,
+            id: drops.len() - 1,
+            drops: self.drops.clone(),
+            panic_on_drop: false,
+        }
+    }
+
+    /// 析构时会 panic 的元素
+    pub(crate) fn panicking_elem(&self, value: i32) -> DropCounter {
+        let mut elem = self.elem(value);
+        elem.panic_on_drop = true;
+        elem
+    }
+
+    pub(crate) fn elems<I: IntoIterator<Item = i32>>(&self, values: I) -> Vec<DropCounter> {
+        values.into_iter().map(|value| self.elem(value)).collect()
+    }
+
+    /// 创建过的元素个数
+    pub(crate) fn created(&self) -> usize {
+        self.drops.borrow().len()
+    }
+
+    /// 已经析构的元素个数
+    pub(crate) fn dropped(&self) -> usize {
+        self.drops.borrow().iter().filter(|&&n| n > 0).count()
+    }
+
+    pub(crate) fn assert_all_dropped_once(&self) {
+        for (id, &n) in self.drops.borrow().iter().enumerate() {
+            assert_eq!(n, 1, "elem #{id} dropped {n} times");
+        }
+    }
+}
+
+/// 记录析构次数的元素, 比较和输出时只看 `value`
+pub(crate) struct DropCounter {
+    pub(crate) value: i32,
+    id: usize,
+    drops: Rc<RefCell<Vec<usize>>>,
+    panic_on_drop: bool,
+}
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.drops.borrow_mut()[self.id] += 1;
+        if self.panic_on_drop {
+            quiet_panic();
+        }
+    }
+}
+
+// clone 出来的是一个新元素, 单独计数
+impl Clone for DropCounter {
+    fn clone(&self) -> Self {
+        let mut drops = self.drops.borrow_mut();
+        drops.push(0);
+        DropCounter {
+            value: self.value,
+            id: drops.len() - 1,
+            drops: self.drops.clone(),
+            panic_on_drop: false,
+        }
+    }
+}
+
+impl PartialEq for DropCounter {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for DropCounter {}
+
+impl PartialOrd for DropCounter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DropCounter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl fmt::Debug for DropCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assert_no_leaks, counts, panics, Tracker};
+
+    #[test]
+    fn counts_allocations() {
+        let (a, d) = counts();
+        let b = Box::new(1);
+        assert_eq!(counts(), (a + 1, d));
+        drop(b);
+        assert_eq!(counts(), (a + 1, d + 1));
+        assert_no_leaks(|| {
+            let mut v = vec![1];
+            // realloc 不计数
+            v.extend(0..100);
+        });
+    }
+
+    // 故意泄漏了一个 Box, miri 会报错
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn detects_leaks() {
+        assert!(panics(|| assert_no_leaks(|| std::mem::forget(Box::new(1)))));
+    }
+
+    #[test]
+    fn tracks_drops() {
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let elems = tracker.elems(0..3);
+            let copy = elems[1].clone();
+            assert_eq!(copy, elems[1]);
+            assert_eq!(tracker.created(), 4);
+            drop(elems);
+            assert_eq!(tracker.dropped(), 3);
+            drop(copy);
+            tracker.assert_all_dropped_once();
+
+            // 析构时 panic 也算析构了一次
+            assert!(panics(|| drop(tracker.panicking_elem(0))));
+            tracker.assert_all_dropped_once();
+        });
+
+        // 断言失败会经过 panic hook, 放在 assert_no_leaks 外面
+        let tracker = Tracker::new();
+        let elem = tracker.elem(0);
+        assert!(panics(|| tracker.assert_all_dropped_once()));
+        drop(elem);
+        tracker.assert_all_dropped_once();
+    }
+}
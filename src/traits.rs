@@ -0,0 +1,186 @@
+//! 各种链表共用的 trait, 方便写泛型代码、替换实现
+//!
+//! [`deque::List`](crate::deque::List) 的节点放在 `RefCell` 里, 取元素时只能拿到
+//! `Ref<T>`/`RefMut<T>`, 所以 peek 返回的类型用关联类型表示,
+//! 只要求它能解引用成 `T`
+
+use std::ops::{Deref, DerefMut};
+
+/// 可变的栈, 只在头部(左端)操作
+pub trait Stack<T> {
+    /// peek 返回的只读引用类型
+    type Ref<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    /// peek 返回的可变引用类型
+    type RefMut<'a>: DerefMut<Target = T>
+    where
+        Self: 'a;
+
+    /// 在头部添加元素
+    fn push_left(&mut self, elem: T);
+
+    /// 从头部移出元素
+    fn pop_left(&mut self) -> Option<T>;
+
+    /// 返回头部元素
+    fn peek_left(&self) -> Option<Self::Ref<'_>>;
+
+    /// 返回头部元素, 可修改
+    fn peek_left_mut(&mut self) -> Option<Self::RefMut<'_>>;
+
+    /// 元素个数, O(1)
+    fn len(&self) -> usize;
+
+    /// 是否为空
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 队列, 在 [`Stack`] 的基础上可以往尾部(右端)添加元素
+pub trait Queue<T>: Stack<T> {
+    /// 在尾部添加元素
+    fn push_right(&mut self, elem: T);
+}
+
+/// 双端队列, 在 [`Queue`] 的基础上两端都可以移出元素
+pub trait Deque<T>: Queue<T> {
+    /// 从尾部移出元素
+    fn pop_right(&mut self) -> Option<T>;
+
+    /// 返回尾部元素
+    fn peek_right(&self) -> Option<Self::Ref<'_>>;
+
+    /// 返回尾部元素, 可修改
+    fn peek_right_mut(&mut self) -> Option<Self::RefMut<'_>>;
+}
+
+/// 不可变的栈, 每次操作都返回一个新版本, 旧版本保持不变
+pub trait PersistentStack<T>: Sized {
+    /// 返回在头部添加了 `elem` 的新版本
+    fn push_left(&self, elem: T) -> Self;
+
+    /// 返回去掉头部元素的新版本, 空栈返回空栈
+    fn pop_left(&self) -> Self;
+
+    /// 返回头部元素
+    fn peek_left(&self) -> Option<&T>;
+
+    /// 元素个数, O(1)
+    fn len(&self) -> usize;
+
+    /// 是否为空
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 不可变的队列, 每次操作都返回一个新版本, 旧版本保持不变
+pub trait PersistentQueue<T>: Sized {
+    /// 返回在尾部添加了 `elem` 的新版本
+    fn push_right(&self, elem: T) -> Self;
+
+    /// 返回去掉头部元素的新版本, 空队列返回空队列
+    fn pop_left(&self) -> Self;
+
+    /// 返回头部元素
+    fn peek_left(&self) -> Option<&T>;
+
+    /// 元素个数, O(1)
+    fn len(&self) -> usize;
+
+    /// 是否为空
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Deque, PersistentStack, Queue, Stack};
+    use crate::{deque, persistent, queue, stack};
+
+    // 只依赖 trait 的泛型代码, 换实现不用改调用的地方
+    fn drain_stack<T, S: Stack<T>>(s: &mut S) -> Vec<T> {
+        let mut out = Vec::new();
+        while let Some(elem) = s.pop_left() {
+            out.push(elem);
+        }
+        out
+    }
+
+    fn bump_left<S: Stack<i32>>(s: &mut S) {
+        if let Some(mut head) = s.peek_left_mut() {
+            *head += 100;
+        }
+    }
+
+    #[test]
+    fn generic_stack() {
+        let mut s = stack::List::new();
+        let mut d = deque::List::new();
+        for i in 0..3 {
+            Stack::push_left(&mut s, i);
+            Stack::push_left(&mut d, i);
+        }
+        bump_left(&mut s);
+        bump_left(&mut d);
+        assert_eq!(*Stack::peek_left(&s).unwrap(), 102);
+        assert_eq!(*Stack::peek_left(&d).unwrap(), 102);
+        assert_eq!(Stack::len(&s), 3);
+        assert_eq!(Stack::len(&d), 3);
+        assert_eq!(drain_stack(&mut s), vec![102, 1, 0]);
+        assert_eq!(drain_stack(&mut d), vec![102, 1, 0]);
+        assert!(Stack::is_empty(&s));
+        assert!(Stack::is_empty(&d));
+    }
+
+    #[test]
+    fn generic_queue() {
+        fn fifo<Q: Queue<i32>>(q: &mut Q) -> Vec<i32> {
+            for i in 1..=3 {
+                q.push_right(i);
+            }
+            drain_stack(q)
+        }
+
+        assert_eq!(fifo(&mut queue::List::new()), vec![1, 2, 3]);
+        assert_eq!(fifo(&mut deque::List::new()), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn generic_deque() {
+        fn fill<D: Deque<i32>>(d: &mut D) {
+            d.push_right(2);
+            d.push_left(1);
+            d.push_right(3);
+            if let Some(mut tail) = d.peek_right_mut() {
+                *tail *= 10;
+            }
+        }
+
+        let mut d = deque::List::new();
+        fill(&mut d);
+        assert_eq!(*Deque::peek_right(&d).unwrap(), 30);
+        assert_eq!(Deque::pop_right(&mut d), Some(30));
+        assert_eq!(drain_stack(&mut d), vec![1, 2]);
+    }
+
+    #[test]
+    fn generic_persistent() {
+        fn versions<P: PersistentStack<i32>>(empty: P) -> (P, P) {
+            let v1 = empty.push_left(1).push_left(2);
+            let v2 = v1.pop_left().push_left(3);
+            (v1, v2)
+        }
+
+        let (v1, v2) = versions(persistent::List::new());
+        assert_eq!(PersistentStack::peek_left(&v1), Some(&2));
+        assert_eq!(PersistentStack::peek_left(&v2), Some(&3));
+        assert_eq!(PersistentStack::peek_left(&v2.pop_left()), Some(&1));
+        assert_eq!(PersistentStack::len(&v1), 2);
+        assert!(PersistentStack::is_empty(&v2.pop_left().pop_left()));
+    }
+}
@@ -0,0 +1,1365 @@
+//! 基于 `NonNull` 裸指针的双向链表
+//!
+//! [`deque::List`](crate::deque::List) 用 `Rc<RefCell<Node<T>>>` 串起节点,
+//! 每个节点都要维护引用计数和借用标记, peek 只能返回 `Ref<T>`/`RefMut<T>`,
+//! 不变量一旦出错就在运行时 panic.
+//!
+//! 这里改用裸指针, 接口和 list9 一样, 但 peek 直接返回 `&T`/`&mut T`:
+//! ```text
+//! [head, tail, len]
+//!    |       |
+//!    v       v
+//!   (1) <-> (2) <-> (3)
+//! ```
+//!
+//! 除了两端的操作, 还可以通过 [`Cursor`]/[`CursorMut`] 在链表中间 O(1) 地插入、删除、拆分和拼接.
+
+use crate::dot::{Dot, Edge};
+use crate::teardown;
+use crate::traits::{Deque, Queue, Stack};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/*
+为什么用 NonNull<Node<T>> 而不是 *mut Node<T>:
+1. Option<NonNull<T>> 和 *mut T 一样大, 但空指针要显式处理, 不会忘记判空
+2. *mut T 对 T 是不变(invariant)的, NonNull<T> 是协变(covariant)的,
+   这样 List<&'static str> 可以当成 List<&'a str> 用, 和 Vec、std 的 LinkedList 一致
+
+PhantomData<T> 告诉编译器 List 拥有 T, drop List 时会 drop T (drop check 需要)
+ */
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+/// 两端都可以 push/pop 的双向链表, 不需要 `RefCell`, peek 直接返回引用
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> List<T> {
+    /// 创建一个空链表
+    pub fn new() -> Self {
+        List {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 元素个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 链表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 在链表头部添加元素
+    pub fn push_left(&mut self, elem: T) {
+        unsafe {
+            // Box::new 分配的内存不会是空指针
+            let new_head = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                elem,
+                next: None,
+                prev: None,
+            })));
+            match self.head {
+                Some(old_head) => {
+                    (*old_head.as_ptr()).prev = Some(new_head);
+                    (*new_head.as_ptr()).next = Some(old_head);
+                }
+                None => {
+                    // 空链表, 头尾都是新节点
+                    self.tail = Some(new_head);
+                }
+            }
+            self.head = Some(new_head);
+            self.len += 1;
+        }
+    }
+
+    /// 在链表尾部添加元素
+    pub fn push_right(&mut self, elem: T) {
+        unsafe {
+            let new_tail = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                elem,
+                next: None,
+                prev: None,
+            })));
+            match self.tail {
+                Some(old_tail) => {
+                    (*old_tail.as_ptr()).next = Some(new_tail);
+                    (*new_tail.as_ptr()).prev = Some(old_tail);
+                }
+                None => {
+                    self.head = Some(new_tail);
+                }
+            }
+            self.tail = Some(new_tail);
+            self.len += 1;
+        }
+    }
+
+    /// 从链表头部移出元素
+    pub fn pop_left(&mut self) -> Option<T> {
+        unsafe {
+            self.head.map(|node| {
+                // 拿回节点的所有权, 离开作用域时释放内存
+                let boxed_node = Box::from_raw(node.as_ptr());
+                self.head = boxed_node.next;
+                match self.head {
+                    Some(new_head) => (*new_head.as_ptr()).prev = None,
+                    // 链表空了, tail 也要清掉
+                    None => self.tail = None,
+                }
+                self.len -= 1;
+                boxed_node.elem
+            })
+        }
+    }
+
+    /// 从链表尾部移出元素
+    pub fn pop_right(&mut self) -> Option<T> {
+        unsafe {
+            self.tail.map(|node| {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                self.tail = boxed_node.prev;
+                match self.tail {
+                    Some(new_tail) => (*new_tail.as_ptr()).next = None,
+                    None => self.head = None,
+                }
+                self.len -= 1;
+                boxed_node.elem
+            })
+        }
+    }
+
+    /// 返回链表头部元素的引用
+    pub fn peek_left(&self) -> Option<&T> {
+        unsafe { self.head.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    /// 返回链表头部元素的可变引用
+    pub fn peek_left_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    /// 返回链表尾部元素的引用
+    pub fn peek_right(&self) -> Option<&T> {
+        unsafe { self.tail.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    /// 返回链表尾部元素的可变引用
+    pub fn peek_right_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    /// 清空链表
+    pub fn clear(&mut self) {
+        teardown::drop_all(self, List::pop_left);
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Debug 和标准库的 LinkedList 一样输出 [1, 2, 3],
+// Display 用 <-> 表示节点之间是双向链接的
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" <-> ")?;
+            }
+            fmt::Display::fmt(elem, f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: fmt::Debug> List<T> {
+    /// 导出 Graphviz DOT 格式的内存布局图, 可以用 `dot -Tsvg` 渲染
+    ///
+    /// 画出 next 和 prev 两个方向的边, 以及链表的 head 和 tail
+    pub fn to_dot(lists: &[(&str, &Self)]) -> String {
+        let mut dot = Dot::new();
+        for (label, list) in lists {
+            let handle = dot.head(label);
+            let mut from = handle.clone();
+            let mut edge = Edge::Head;
+            let mut cur = list.head;
+            while let Some(ptr) = cur {
+                // 安全: 只读, &self 保证期间没有人修改链表
+                let node = unsafe { ptr.as_ref() };
+                let (name, fresh) = dot.node(ptr.as_ptr(), &node.elem, None);
+                dot.edge(&from, &name, edge);
+                if !fresh {
+                    break;
+                }
+                if let Some(prev) = node.prev.and_then(|prev| dot.name(prev.as_ptr())) {
+                    dot.edge(&name, &prev, Edge::Prev);
+                }
+                from = name;
+                edge = Edge::Next;
+                cur = node.next;
+            }
+            if let Some(tail) = list.tail.and_then(|tail| dot.name(tail.as_ptr())) {
+                dot.edge(&handle, &tail, Edge::Tail);
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// 和 Box、Vec 一样: 链表拥有所有节点, 所以只要 T 能跨线程, 链表就能跨线程
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+impl<T> Stack<T> for List<T> {
+    type Ref<'a> = &'a T where T: 'a;
+    type RefMut<'a> = &'a mut T where T: 'a;
+
+    fn push_left(&mut self, elem: T) {
+        List::push_left(self, elem)
+    }
+
+    fn pop_left(&mut self) -> Option<T> {
+        List::pop_left(self)
+    }
+
+    fn peek_left(&self) -> Option<&T> {
+        List::peek_left(self)
+    }
+
+    fn peek_left_mut(&mut self) -> Option<&mut T> {
+        List::peek_left_mut(self)
+    }
+
+    fn len(&self) -> usize {
+        List::len(self)
+    }
+}
+
+impl<T> Queue<T> for List<T> {
+    fn push_right(&mut self, elem: T) {
+        List::push_right(self, elem)
+    }
+}
+
+impl<T> Deque<T> for List<T> {
+    fn pop_right(&mut self) -> Option<T> {
+        List::pop_right(self)
+    }
+
+    fn peek_right(&self) -> Option<&T> {
+        List::peek_right(self)
+    }
+
+    fn peek_right_mut(&mut self) -> Option<&mut T> {
+        List::peek_right_mut(self)
+    }
+}
+
+/// 按不可变引用迭代, 两端都可以取, 由 [`List::iter`] 创建
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    // 剩余元素个数, 两端的游标相遇时它正好减到 0
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的引用
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &(*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+/// 按可变引用迭代, 两端都可以取, 由 [`List::iter_mut`] 创建
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> List<T> {
+    /// 从头到尾返回元素的可变引用
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+// Iter/IterMut 里面也是裸指针, 要手动声明, 条件和 &T/&mut T 一样
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+/// 按值迭代, 两端都可以取, 由 [`List::into_iter`](IntoIterator::into_iter) 创建
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_left()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_right()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/*
+游标可以停在任意一个节点上, 在 O(1) 时间内读写、插入、删除、拆分和拼接
+
+游标的位置在 "幽灵" 位置(ghost)和真实节点之间循环:
+    ghost -> (0) -> (1) -> ... -> (len-1) -> ghost -> (0) ...
+ghost 位于 tail 和 head 之间, 不对应任何元素, 此时 index 为 None
+ */
+
+/// 只读游标, 由 [`List::cursor_left`]/[`List::cursor_right`] 创建
+pub struct Cursor<'a, T> {
+    cur: Link<T>,
+    list: &'a List<T>,
+    index: Option<usize>,
+}
+
+impl<T> List<T> {
+    /// 返回指向头部元素的只读游标, 空链表时指向 ghost
+    pub fn cursor_left(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.head,
+            list: self,
+            index: self.head.map(|_| 0),
+        }
+    }
+
+    /// 返回指向尾部元素的只读游标, 空链表时指向 ghost
+    pub fn cursor_right(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.tail,
+            list: self,
+            index: self.tail.map(|_| self.len - 1),
+        }
+    }
+
+    /// 返回指向头部元素的游标, 可以修改链表, 空链表时指向 ghost
+    pub fn cursor_left_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.head,
+            index: self.head.map(|_| 0),
+            list: self,
+        }
+    }
+
+    /// 返回指向尾部元素的游标, 可以修改链表, 空链表时指向 ghost
+    pub fn cursor_right_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.tail,
+            index: self.tail.map(|_| self.len - 1),
+            list: self,
+        }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// 当前元素的下标, 在 ghost 上时返回 None
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// 移到下一个位置, 从最后一个元素会移到 ghost, 从 ghost 会移到头部
+    pub fn move_next(&mut self) {
+        match self.cur {
+            Some(cur) => unsafe {
+                self.cur = (*cur.as_ptr()).next;
+                self.index = match self.cur {
+                    Some(_) => self.index.map(|i| i + 1),
+                    None => None,
+                };
+            },
+            None => {
+                self.cur = self.list.head;
+                self.index = self.cur.map(|_| 0);
+            }
+        }
+    }
+
+    /// 移到上一个位置, 从第一个元素会移到 ghost, 从 ghost 会移到尾部
+    pub fn move_prev(&mut self) {
+        match self.cur {
+            Some(cur) => unsafe {
+                self.cur = (*cur.as_ptr()).prev;
+                self.index = match self.cur {
+                    Some(_) => self.index.map(|i| i - 1),
+                    None => None,
+                };
+            },
+            None => {
+                self.cur = self.list.tail;
+                self.index = self.cur.map(|_| self.list.len - 1);
+            }
+        }
+    }
+
+    /// 当前元素, 在 ghost 上时返回 None
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    /// 下一个元素, 不移动游标
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).next,
+                None => self.list.head,
+            };
+            next.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    /// 上一个元素, 不移动游标
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+/// 可以修改链表的游标, 由 [`List::cursor_left_mut`]/[`List::cursor_right_mut`] 创建
+pub struct CursorMut<'a, T> {
+    cur: Link<T>,
+    list: &'a mut List<T>,
+    index: Option<usize>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// 当前元素的下标, 在 ghost 上时返回 None
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// 移到下一个位置, 从最后一个元素会移到 ghost, 从 ghost 会移到头部
+    pub fn move_next(&mut self) {
+        match self.cur {
+            Some(cur) => unsafe {
+                self.cur = (*cur.as_ptr()).next;
+                self.index = match self.cur {
+                    Some(_) => self.index.map(|i| i + 1),
+                    None => None,
+                };
+            },
+            None => {
+                self.cur = self.list.head;
+                self.index = self.cur.map(|_| 0);
+            }
+        }
+    }
+
+    /// 移到上一个位置, 从第一个元素会移到 ghost, 从 ghost 会移到尾部
+    pub fn move_prev(&mut self) {
+        match self.cur {
+            Some(cur) => unsafe {
+                self.cur = (*cur.as_ptr()).prev;
+                self.index = match self.cur {
+                    Some(_) => self.index.map(|i| i - 1),
+                    None => None,
+                };
+            },
+            None => {
+                self.cur = self.list.tail;
+                self.index = self.cur.map(|_| self.list.len - 1);
+            }
+        }
+    }
+
+    /// 当前元素, 在 ghost 上时返回 None
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    /// 下一个元素, 不移动游标
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).next,
+                None => self.list.head,
+            };
+            next.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    /// 上一个元素, 不移动游标
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    /// 返回一个只读游标, 位置和当前游标相同
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    /// 在当前元素前面插入, 在 ghost 上时插到链表尾部
+    pub fn insert_before(&mut self, elem: T) {
+        let Some(cur) = self.cur else {
+            self.list.push_right(elem);
+            return;
+        };
+        unsafe {
+            match (*cur.as_ptr()).prev {
+                None => self.list.push_left(elem),
+                Some(prev) => {
+                    let node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                        elem,
+                        next: Some(cur),
+                        prev: Some(prev),
+                    })));
+                    (*prev.as_ptr()).next = Some(node);
+                    (*cur.as_ptr()).prev = Some(node);
+                    self.list.len += 1;
+                }
+            }
+        }
+        // 前面多了一个元素, 当前元素的下标也要加一
+        self.index = self.index.map(|i| i + 1);
+    }
+
+    /// 在当前元素后面插入, 在 ghost 上时插到链表头部
+    pub fn insert_after(&mut self, elem: T) {
+        let Some(cur) = self.cur else {
+            self.list.push_left(elem);
+            return;
+        };
+        unsafe {
+            match (*cur.as_ptr()).next {
+                None => self.list.push_right(elem),
+                Some(next) => {
+                    let node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                        elem,
+                        next: Some(next),
+                        prev: Some(cur),
+                    })));
+                    (*next.as_ptr()).prev = Some(node);
+                    (*cur.as_ptr()).next = Some(node);
+                    self.list.len += 1;
+                }
+            }
+        }
+    }
+
+    /// 移除当前元素, 游标移到下一个元素上(没有下一个元素就移到 ghost)
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        unsafe {
+            let boxed_node = Box::from_raw(cur.as_ptr());
+            let (prev, next) = (boxed_node.prev, boxed_node.next);
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.list.head = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.list.tail = prev,
+            }
+            self.list.len -= 1;
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+            Some(boxed_node.elem)
+        }
+    }
+
+    /// 把当前元素之前的部分拆出来作为新链表返回, 在 ghost 上时返回整个链表
+    ///
+    /// ```text
+    /// list.split_before(): [1, 2, |3|, 4] -> [|3|, 4], 返回 [1, 2]
+    /// ```
+    pub fn split_before(&mut self) -> List<T> {
+        let Some(cur) = self.cur else {
+            return std::mem::take(self.list);
+        };
+        unsafe {
+            let Some(prev) = (*cur.as_ptr()).prev else {
+                // 当前就是头部元素, 前面什么都没有
+                return List::new();
+            };
+            let output_len = self.index.unwrap();
+            let output = List {
+                head: self.list.head,
+                tail: Some(prev),
+                len: output_len,
+                _marker: PhantomData,
+            };
+
+            // 在 prev 和 cur 之间断开
+            (*prev.as_ptr()).next = None;
+            (*cur.as_ptr()).prev = None;
+            self.list.head = Some(cur);
+            self.list.len -= output_len;
+            self.index = Some(0);
+            output
+        }
+    }
+
+    /// 把当前元素之后的部分拆出来作为新链表返回, 在 ghost 上时返回整个链表
+    ///
+    /// ```text
+    /// list.split_after(): [1, 2, |3|, 4] -> [1, 2, |3|], 返回 [4]
+    /// ```
+    pub fn split_after(&mut self) -> List<T> {
+        let Some(cur) = self.cur else {
+            return std::mem::take(self.list);
+        };
+        unsafe {
+            let Some(next) = (*cur.as_ptr()).next else {
+                return List::new();
+            };
+            let remain_len = self.index.unwrap() + 1;
+            let output = List {
+                head: Some(next),
+                tail: self.list.tail,
+                len: self.list.len - remain_len,
+                _marker: PhantomData,
+            };
+
+            (*cur.as_ptr()).next = None;
+            (*next.as_ptr()).prev = None;
+            self.list.tail = Some(cur);
+            self.list.len = remain_len;
+            output
+        }
+    }
+
+    /// 把 `input` 整个拼接到当前元素前面, 在 ghost 上时拼到链表尾部
+    pub fn splice_before(&mut self, mut input: List<T>) {
+        let (Some(in_head), Some(in_tail)) = (input.head.take(), input.tail.take()) else {
+            return;
+        };
+        let in_len = std::mem::take(&mut input.len);
+        unsafe {
+            match self.cur {
+                Some(cur) => {
+                    match (*cur.as_ptr()).prev {
+                        Some(prev) => {
+                            (*prev.as_ptr()).next = Some(in_head);
+                            (*in_head.as_ptr()).prev = Some(prev);
+                        }
+                        None => self.list.head = Some(in_head),
+                    }
+                    (*cur.as_ptr()).prev = Some(in_tail);
+                    (*in_tail.as_ptr()).next = Some(cur);
+                    self.index = self.index.map(|i| i + in_len);
+                }
+                None => match self.list.tail {
+                    Some(tail) => {
+                        (*tail.as_ptr()).next = Some(in_head);
+                        (*in_head.as_ptr()).prev = Some(tail);
+                        self.list.tail = Some(in_tail);
+                    }
+                    None => {
+                        self.list.head = Some(in_head);
+                        self.list.tail = Some(in_tail);
+                    }
+                },
+            }
+        }
+        self.list.len += in_len;
+    }
+
+    /// 把 `input` 整个拼接到当前元素后面, 在 ghost 上时拼到链表头部
+    pub fn splice_after(&mut self, mut input: List<T>) {
+        let (Some(in_head), Some(in_tail)) = (input.head.take(), input.tail.take()) else {
+            return;
+        };
+        let in_len = std::mem::take(&mut input.len);
+        unsafe {
+            match self.cur {
+                Some(cur) => {
+                    match (*cur.as_ptr()).next {
+                        Some(next) => {
+                            (*next.as_ptr()).prev = Some(in_tail);
+                            (*in_tail.as_ptr()).next = Some(next);
+                        }
+                        None => self.list.tail = Some(in_tail),
+                    }
+                    (*cur.as_ptr()).next = Some(in_head);
+                    (*in_head.as_ptr()).prev = Some(cur);
+                }
+                None => match self.list.head {
+                    Some(head) => {
+                        (*head.as_ptr()).prev = Some(in_tail);
+                        (*in_tail.as_ptr()).next = Some(head);
+                        self.list.head = Some(in_head);
+                    }
+                    None => {
+                        self.list.head = Some(in_head);
+                        self.list.tail = Some(in_tail);
+                    }
+                },
+            }
+        }
+        self.list.len += in_len;
+    }
+}
+
+unsafe impl<T: Sync> Send for Cursor<'_, T> {}
+unsafe impl<T: Sync> Sync for Cursor<'_, T> {}
+unsafe impl<T: Send> Send for CursorMut<'_, T> {}
+unsafe impl<T: Sync> Sync for CursorMut<'_, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<List<i32>>();
+    is_sync::<List<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    is_send::<Cursor<i32>>();
+    is_sync::<Cursor<i32>>();
+
+    is_send::<CursorMut<i32>>();
+    is_sync::<CursorMut<i32>>();
+
+    // 协变: 长生命周期的可以当成短生命周期的用
+    fn list_covariant<'a, T>(x: List<&'static T>) -> List<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+    fn into_iter_covariant<'a, T>(x: IntoIter<&'static T>) -> IntoIter<&'a T> {
+        x
+    }
+}
+
+/// IterMut 必须是不变的, 否则可以往里面塞一个短生命周期的引用
+///
+/// ```compile_fail
+/// use rust_linklist::unsafe_deque::IterMut;
+///
+/// fn iter_mut_covariant<'i, 'a, T>(x: IterMut<'i, &'static T>) -> IterMut<'i, &'a T> { x }
+/// ```
+#[allow(dead_code)]
+fn iter_mut_invariant() {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_left(), None);
+        assert_eq!(list.len(), 0);
+
+        // Populate list
+        list.push_left(10);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_left(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_left(), None);
+
+        list.push_left(10);
+        list.push_left(20);
+        list.push_left(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_left(), Some(30));
+        list.push_left(40);
+        assert_eq!(list.pop_left(), Some(40));
+        assert_eq!(list.pop_left(), Some(20));
+        assert_eq!(list.pop_left(), Some(10));
+        assert_eq!(list.pop_left(), None);
+        assert_eq!(list.len(), 0);
+
+        // ---- back -----
+
+        list.push_right(10);
+        assert_eq!(list.pop_right(), Some(10));
+        assert_eq!(list.pop_right(), None);
+
+        list.push_right(10);
+        list.push_right(20);
+        list.push_right(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_right(), Some(30));
+        list.push_right(40);
+        assert_eq!(list.pop_right(), Some(40));
+        assert_eq!(list.pop_right(), Some(20));
+        assert_eq!(list.pop_right(), Some(10));
+        assert_eq!(list.pop_right(), None);
+
+        // ---- mixed -----
+
+        list.push_right(2);
+        list.push_left(1);
+        list.push_right(3);
+        assert_eq!(list.pop_right(), Some(3));
+        assert_eq!(list.pop_left(), Some(1));
+        assert_eq!(list.pop_right(), Some(2));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek_left(), None);
+        assert_eq!(list.peek_right(), None);
+        assert_eq!(list.peek_left_mut(), None);
+        assert_eq!(list.peek_right_mut(), None);
+
+        list.push_left(1);
+        list.push_left(2);
+        list.push_left(3);
+
+        assert_eq!(list.peek_left(), Some(&3));
+        assert_eq!(list.peek_right(), Some(&1));
+        *list.peek_left_mut().unwrap() *= 10;
+        *list.peek_right_mut().unwrap() *= 10;
+        assert_eq!(list.pop_left(), Some(30));
+        assert_eq!(list.pop_right(), Some(10));
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        for i in 0..5 {
+            list.push_right(i);
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(list.iter().rev().collect::<Vec<_>>(), vec![&4, &3, &2, &1, &0]);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() += 1;
+        *iter.next().unwrap() += 2;
+        assert_eq!(iter.len(), 3);
+
+        let mut sum = 0;
+        for elem in &list {
+            sum += *elem;
+        }
+        assert_eq!(sum, 103);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        list.push_right(1);
+        list.push_right(2);
+        list.push_right(3);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    fn from_slice(v: &[i32]) -> List<i32> {
+        let mut list = List::new();
+        for &x in v {
+            list.push_right(x);
+        }
+        list
+    }
+
+    fn check_links(list: &List<i32>) {
+        // 正着走和倒着走结果一致, 长度也对得上
+        let forward: Vec<_> = list.iter().collect();
+        let mut backward: Vec<_> = list.iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), list.len());
+    }
+
+    fn to_vec(list: &List<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn cursor_move_peek() {
+        let list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = list.cursor_left();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_next(), Some(&2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&2));
+        assert_eq!(cursor.peek_next(), Some(&3));
+        assert_eq!(cursor.peek_prev(), Some(&1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let mut cursor = list.cursor_right();
+        assert_eq!(cursor.current(), Some(&6));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&5));
+        assert_eq!(cursor.index(), Some(5));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&5));
+        assert_eq!(cursor.index(), Some(4));
+
+        let empty = List::<i32>::new();
+        let mut cursor = empty.cursor_left();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+    }
+
+    #[test]
+    fn cursor_mut_modify() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = list.cursor_left_mut();
+        cursor.move_next();
+        *cursor.current().unwrap() = 20;
+        *cursor.peek_next().unwrap() = 30;
+        *cursor.peek_prev().unwrap() = 10;
+        assert_eq!(cursor.as_cursor().current(), Some(&20));
+        assert_eq!(to_vec(&list), vec![10, 20, 30, 4, 5, 6]);
+    }
+
+    #[test]
+    fn cursor_mut_insert_remove() {
+        let mut list = from_slice(&[1, 2, 3]);
+        let mut cursor = list.cursor_left_mut();
+        cursor.move_next();
+        cursor.insert_before(7);
+        cursor.insert_after(8);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_links(&list);
+        assert_eq!(to_vec(&list), vec![1, 7, 2, 8, 3]);
+
+        let mut cursor = list.cursor_left_mut();
+        cursor.move_prev();
+        // ghost 上插入: before 插到尾部, after 插到头部
+        cursor.insert_before(9);
+        cursor.insert_after(10);
+        assert_eq!(cursor.index(), None);
+        check_links(&list);
+        assert_eq!(to_vec(&list), vec![10, 1, 7, 2, 8, 3, 9]);
+
+        let mut cursor = list.cursor_left_mut();
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        assert_eq!(cursor.index(), Some(2));
+        check_links(&list);
+
+        let mut cursor = list.cursor_right_mut();
+        assert_eq!(cursor.remove_current(), Some(9));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.remove_current(), None);
+        check_links(&list);
+        assert_eq!(to_vec(&list), vec![1, 7, 8, 3]);
+
+        // 删到只剩空链表
+        let mut cursor = list.cursor_left_mut();
+        while cursor.remove_current().is_some() {}
+        assert!(list.is_empty());
+        assert_eq!(list.peek_left(), None);
+        assert_eq!(list.peek_right(), None);
+        list.push_right(1);
+        check_links(&list);
+    }
+
+    #[test]
+    fn cursor_mut_split() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = list.cursor_left_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(to_vec(&before), vec![1, 2]);
+        assert_eq!(to_vec(&after), vec![4, 5, 6]);
+        assert_eq!(to_vec(&list), vec![3]);
+        check_links(&before);
+        check_links(&after);
+        check_links(&list);
+
+        // 头尾和 ghost 上拆分
+        let mut list = from_slice(&[1, 2, 3]);
+        let mut cursor = list.cursor_left_mut();
+        assert!(cursor.split_before().is_empty());
+        let mut cursor = list.cursor_right_mut();
+        assert!(cursor.split_after().is_empty());
+        cursor.move_next();
+        let all = cursor.split_after();
+        assert_eq!(to_vec(&all), vec![1, 2, 3]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn cursor_mut_splice() {
+        let mut list = from_slice(&[1, 2, 3]);
+        let mut cursor = list.cursor_left_mut();
+        cursor.move_next();
+        cursor.splice_before(from_slice(&[7, 8]));
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.splice_after(from_slice(&[9]));
+        cursor.splice_after(List::new());
+        check_links(&list);
+        assert_eq!(to_vec(&list), vec![1, 7, 8, 2, 9, 3]);
+
+        let mut cursor = list.cursor_left_mut();
+        cursor.splice_before(from_slice(&[0]));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        cursor.splice_before(from_slice(&[10, 11]));
+        cursor.splice_after(from_slice(&[-1]));
+        check_links(&list);
+        assert_eq!(to_vec(&list), vec![-1, 0, 1, 7, 8, 2, 9, 3, 10, 11]);
+
+        let mut empty = List::new();
+        empty.cursor_left_mut().splice_after(from_slice(&[1, 2]));
+        empty.cursor_right_mut().splice_before(from_slice(&[3]));
+        check_links(&empty);
+        assert_eq!(to_vec(&empty), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn send_across_threads() {
+        let mut list = List::new();
+        for i in 0..100 {
+            list.push_right(i);
+        }
+        let sum = std::thread::spawn(move || list.iter().sum::<i32>()).join().unwrap();
+        assert_eq!(sum, 4950);
+    }
+
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push_right(1);
+        list.push_left(0);
+        list.push_right(2);
+
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        let head = list.peek_left_mut().unwrap();
+        *head += 1;
+        assert_eq!(list.peek_left(), Some(&1));
+
+        let mut iter = list.iter_mut();
+        let a = iter.next().unwrap();
+        let b = iter.next_back().unwrap();
+        // 同时持有两端的可变引用
+        *a += 100;
+        *b += 100;
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![101, 10, 120]);
+
+        assert_eq!(list.pop_right(), Some(120));
+        list.push_right(3);
+        assert_eq!(list.pop_left(), Some(101));
+        // Drop it on the ground and let the dtor exercise itself
+    }
+
+    #[test]
+    fn fmt() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        assert_eq!(list.to_string(), "[]");
+        list.push_right(2);
+        list.push_right(3);
+        list.push_left(1);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(format!("{:#?}", list), "[\n    1,\n    2,\n    3,\n]");
+        assert_eq!(list.to_string(), "[1 <-> 2 <-> 3]");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list.push_right(i.to_string());
+        }
+        drop(list);
+    }
+
+    #[test]
+    fn drops_each_elem_once() {
+        use crate::testkit::{assert_no_leaks, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..50) {
+                list.push_right(elem);
+            }
+            list.push_left(tracker.elem(-1));
+            drop(list.pop_left());
+            drop(list.pop_right());
+            assert_eq!(list.iter().rev().count(), 49);
+
+            let mut cursor = list.cursor_left_mut();
+            for _ in 0..10 {
+                cursor.move_next();
+            }
+            drop(cursor.remove_current());
+            cursor.insert_before(tracker.elem(100));
+            cursor.insert_after(tracker.elem(101));
+            let front = cursor.split_before();
+            cursor.splice_after(front);
+            let back = cursor.split_after();
+            drop(back);
+
+            let mut iter = list.into_iter();
+            drop(iter.next());
+            drop(iter.next_back());
+            drop(iter);
+            tracker.assert_all_dropped_once();
+        });
+    }
+
+    #[test]
+    fn panic_in_elem_drop() {
+        use crate::testkit::{assert_no_leaks, panics, Tracker};
+
+        assert_no_leaks(|| {
+            let tracker = Tracker::new();
+            let mut list = List::new();
+            for elem in tracker.elems(0..10) {
+                list.push_left(elem);
+            }
+            list.push_left(tracker.panicking_elem(10));
+            for elem in tracker.elems(11..20) {
+                list.push_left(elem);
+            }
+            assert!(panics(|| list.clear()));
+            assert!(list.is_empty());
+            tracker.assert_all_dropped_once();
+
+            list.push_right(tracker.panicking_elem(20));
+            list.push_right(tracker.elem(21));
+            assert!(panics(|| drop(list)));
+            tracker.assert_all_dropped_once();
+        });
+    }
+}
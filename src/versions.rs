@@ -0,0 +1,98 @@
+//! [`persistent`](crate::persistent) 和 [`persistent_sync`](crate::persistent_sync) 共用的
+//! `SharedVersions` 编码
+//!
+//! 两种链表只是指针类型不同(`Rc` 和 `Arc`), 各自通过 [`Versioned`] 提供遍历节点和建节点的操作,
+//! 给节点编号、按编号重建共享关系的逻辑都在这里
+
+use std::collections::HashMap;
+
+// 序列化格式里的 nodes: (元素, next 的下标)
+pub(crate) type Nodes<E> = Vec<(E, Option<usize>)>;
+
+pub(crate) trait Versioned: Sized {
+    type Elem;
+    /// 节点本身, 用它的地址判断两个链表是不是共享同一个节点
+    type Node;
+    /// 指向节点的共享指针, 比如 `Rc<Node<T>>`
+    type Link: Clone;
+
+    fn head(&self) -> Option<&Self::Node>;
+
+    fn next(node: &Self::Node) -> Option<&Self::Node>;
+
+    fn elem(node: &Self::Node) -> &Self::Elem;
+
+    /// 新建一个节点, 挂在 `next` 前面
+    fn link(elem: Self::Elem, next: Option<Self::Link>) -> Self::Link;
+
+    /// 用头节点组装出一个链表
+    fn from_head(head: Option<Self::Link>) -> Self;
+}
+
+// 给所有版本的节点编号, 返回 (nodes, heads)
+pub(crate) fn encode<L: Versioned>(lists: &[L]) -> (Nodes<&L::Elem>, Vec<Option<usize>>) {
+    let mut ids: HashMap<*const L::Node, usize> = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut heads = Vec::with_capacity(lists.len());
+    for list in lists {
+        // 从头往后走, 直到遇到已经编过号的节点, 后面的部分都是共享的
+        let mut fresh = Vec::new();
+        let mut shared = None;
+        let mut cur = list.head();
+        while let Some(node) = cur {
+            if let Some(&id) = ids.get(&(node as *const L::Node)) {
+                shared = Some(id);
+                break;
+            }
+            fresh.push(node);
+            cur = L::next(node);
+        }
+        // 倒着编号, 越靠近尾部编号越小
+        let mut next = shared;
+        for node in fresh.into_iter().rev() {
+            let id = nodes.len();
+            nodes.push((L::elem(node), next));
+            ids.insert(node, id);
+            next = Some(id);
+        }
+        heads.push(next);
+    }
+    (nodes, heads)
+}
+
+// encode 的逆过程, 下标不合法时返回错误信息
+pub(crate) fn decode<L: Versioned>(
+    nodes: Nodes<L::Elem>,
+    heads: Vec<Option<usize>>,
+) -> Result<Vec<L>, String> {
+    let mut built: Vec<L::Link> = Vec::with_capacity(nodes.len());
+    let mut checked = Ok(());
+    for (id, (elem, next)) in nodes.into_iter().enumerate() {
+        let next = match next {
+            None => None,
+            Some(next) if next < id => Some(built[next].clone()),
+            Some(next) => {
+                checked = Err(format!("node {id} links to node {next}, which is not defined before it"));
+                break;
+            }
+        };
+        built.push(L::link(elem, next));
+    }
+
+    let lists = checked.and_then(|()| heads
+        .into_iter()
+        .map(|head| match head {
+            None => Ok(L::from_head(None)),
+            Some(id) => built
+                .get(id)
+                .map(|node| L::from_head(Some(node.clone())))
+                .ok_or_else(|| format!("head links to node {id}, but there are only {} nodes", built.len())),
+        })
+        .collect::<Result<Vec<_>, _>>());
+
+    // 不管成功与否都从编号大的开始释放: 没被任何版本用到的节点被释放时,
+    // 它的 next 还在 built 里, 引用计数不会归零, 所以不会沿着链表递归 drop
+    // 直接 drop(built) 是从编号小的开始, 最后一个节点会带着整条链递归释放
+    while built.pop().is_some() {}
+    lists
+}